@@ -0,0 +1,60 @@
+//! Send a command to a running rocketbar, e.g. `rocketbar-ctl timer start 25m`.
+
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Default socket path, matching the bar's own default.
+fn default_socket() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("rocketbar.sock"),
+        _ => env::temp_dir().join("rocketbar.sock"),
+    }
+}
+
+/// Quote an argument so the bar splits it back into the same word.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        return arg.to_string();
+    }
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{escaped}\"")
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let mut socket = default_socket();
+    if args.first().is_some_and(|a| a == "--socket") && args.len() >= 2 {
+        socket = PathBuf::from(args.remove(1));
+        args.remove(0);
+    }
+    if args.is_empty() {
+        eprintln!("usage: rocketbar-ctl [--socket PATH] <command> [args...]");
+        return ExitCode::FAILURE;
+    }
+
+    let line = args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(" ");
+    let mut stream = match UnixStream::connect(&socket) {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("rocketbar-ctl: {}: {e}", socket.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = writeln!(stream, "{line}") {
+        eprintln!("rocketbar-ctl: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut reply = String::new();
+    let _ = BufReader::new(&stream).read_line(&mut reply);
+    let reply = reply.trim();
+    if reply == "ok" {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("rocketbar-ctl: {reply}");
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,195 @@
+//! A single entry of the status line and its i3bar serialization.
+
+use crate::config::{Display, MergeConfig, StyleConfig};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// One block of the bar, kept as parts until it is serialized so spacing and
+/// padding can be applied in one place.
+#[derive(Clone)]
+pub struct Block {
+    pub name: String,
+    /// Tells apart blocks sharing a name, e.g. the mount point of a disk, so
+    /// click events can be routed to the right one.
+    pub instance: Option<String>,
+    pub icon: String,
+    pub text: String,
+    /// `text` with pango markup, used instead of it in i3bar output.
+    pub markup: Option<String>,
+    pub color: Option<String>,
+    /// Asks the bar to highlight the block, e.g. past a critical threshold.
+    pub urgent: bool,
+    /// The number shown, for conditions like `hide_when`.
+    pub value: Option<f32>,
+    /// The value as a percentage, for outputs that can draw it as a gauge.
+    pub percentage: Option<f32>,
+    /// Longer details, for outputs that support tooltips.
+    pub tooltip: Option<String>,
+}
+
+impl Block {
+    pub fn new(name: impl Into<String>, icon: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            instance: None,
+            icon: icon.into(),
+            text: text.into(),
+            markup: None,
+            color: None,
+            urgent: false,
+            value: None,
+            percentage: None,
+            tooltip: None,
+        }
+    }
+
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    pub fn markup(mut self, markup: impl Into<String>) -> Self {
+        self.markup = Some(markup.into());
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn value(mut self, value: f32) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Set a percentage, which is also the block's value.
+    pub fn percentage(mut self, percentage: f32) -> Self {
+        self.value = Some(percentage);
+        self.percentage = Some(percentage);
+        self
+    }
+
+    pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Mark the block urgent when `value` reaches the configured threshold.
+    pub fn urgent_at(mut self, value: f32, threshold: Option<f32>) -> Self {
+        self.urgent = threshold.is_some_and(|t| value >= t);
+        self
+    }
+
+    /// Join icon and text with the configured spacing.
+    pub fn body(&self, style: &StyleConfig) -> String {
+        self.join(&self.text, style)
+    }
+
+    fn join(&self, text: &str, style: &StyleConfig) -> String {
+        let overrides = style.blocks.get(&self.name);
+        let spacing = overrides
+            .and_then(|o| o.icon_spacing.as_deref())
+            .unwrap_or(&style.icon_spacing);
+        match overrides.and_then(|o| o.display) {
+            Some(Display::IconOnly) if !self.icon.is_empty() => return self.icon.clone(),
+            Some(Display::TextOnly) => return text.to_string(),
+            _ => {}
+        }
+        match (self.icon.is_empty(), text.is_empty()) {
+            (true, _) => text.to_string(),
+            (false, true) => self.icon.clone(),
+            (false, false) => format!("{}{}{}", self.icon, spacing, text),
+        }
+    }
+
+    /// Join icon and text with the configured spacing and add padding.
+    pub fn full_text(&self, style: &StyleConfig) -> String {
+        self.pad(self.body(style), style)
+    }
+
+    fn pad(&self, body: String, style: &StyleConfig) -> String {
+        let overrides = style.blocks.get(&self.name);
+        let left = overrides
+            .and_then(|o| o.padding_left)
+            .unwrap_or(style.padding_left);
+        let right = overrides
+            .and_then(|o| o.padding_right)
+            .unwrap_or(style.padding_right);
+        format!("{}{}{}", " ".repeat(left), body, " ".repeat(right))
+    }
+
+    /// Serialize as an i3bar protocol block.
+    pub fn to_json(&self, style: &StyleConfig) -> Value {
+        let full_text = match &self.markup {
+            Some(markup) => self.pad(self.join(markup, style), style),
+            None => self.full_text(style),
+        };
+        let mut block = json!({
+            "full_text": full_text,
+            "name": self.name,
+        });
+        if self.markup.is_some() {
+            block["markup"] = json!("pango");
+        }
+        if let Some(instance) = &self.instance {
+            block["instance"] = json!(instance);
+        }
+        if let Some(color) = &self.color {
+            block["color"] = json!(color);
+        }
+        if let Some(min_width) = style
+            .blocks
+            .get(&self.name)
+            .and_then(|o| o.min_width.as_ref())
+        {
+            block["min_width"] = json!(min_width);
+        }
+        if self.urgent {
+            block["urgent"] = json!(true);
+        }
+        block
+    }
+}
+
+/// Combine the members of each merge into one block, placed where the first
+/// member present would have been. The merged block takes the first member
+/// color and is urgent if any member is.
+pub fn merge(
+    blocks: Vec<Block>,
+    merges: &BTreeMap<String, MergeConfig>,
+    style: &StyleConfig,
+) -> Vec<Block> {
+    let mut merged: Vec<Block> = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let Some((name, cfg)) = merges.iter().find(|(_, m)| m.blocks.contains(&block.name)) else {
+            merged.push(block);
+            continue;
+        };
+        let part = if cfg.icons {
+            block.body(style)
+        } else {
+            block.text.clone()
+        };
+        match merged.iter_mut().find(|b| &b.name == name) {
+            Some(target) => {
+                target.text = format!("{}{}{}", target.text, cfg.joiner, part);
+                target.color = target.color.take().or(block.color);
+                target.urgent |= block.urgent;
+            }
+            None => merged.push(Block {
+                name: name.clone(),
+                instance: None,
+                icon: cfg.icon.clone(),
+                text: part,
+                markup: None,
+                color: block.color,
+                urgent: block.urgent,
+                value: None,
+                percentage: None,
+                tooltip: None,
+            }),
+        }
+    }
+    merged
+}
@@ -0,0 +1,37 @@
+//! Battery charge from `/sys/class/power_supply`.
+
+use std::fs;
+
+#[derive(Clone)]
+pub struct Battery {
+    pub percent: f32,
+    pub charging: bool,
+}
+
+/// Read the charge of `device`, e.g. `BAT0`. Returns `None` on machines
+/// without that battery.
+pub fn get_battery(device: &str) -> Option<Battery> {
+    let dir = format!("/sys/class/power_supply/{device}");
+    let percent = fs::read_to_string(format!("{dir}/capacity"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let status = fs::read_to_string(format!("{dir}/status")).unwrap_or_default();
+    Some(Battery {
+        percent,
+        charging: status.trim() == "Charging",
+    })
+}
+
+/// Shown instead of the level icon while charging.
+pub const CHARGING_ICON: &str = "󰂄";
+
+/// Built-in icons by level while discharging.
+pub const RAMP: &[(f32, &str)] = &[
+    (0.0, ""),
+    (15.0, ""),
+    (40.0, ""),
+    (65.0, ""),
+    (90.0, ""),
+];
@@ -0,0 +1,40 @@
+//! The active binding mode under sway or i3, like `resize`.
+
+use crate::config::SharedConfig;
+use crate::wm;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Follow mode events and keep `slot` set to the active mode. Does nothing
+/// without a window manager; waits for the block to be enabled before
+/// subscribing.
+pub fn spawn_subscriber(
+    slot: Arc<Mutex<Option<String>>>,
+    config: SharedConfig,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    if !wm::available() {
+        return;
+    }
+    thread::spawn(move || {
+        while !config.read().unwrap().binding_mode.enabled {
+            thread::sleep(Duration::from_secs(5));
+        }
+        let set = |mode: Option<String>| {
+            let mut slot = slot.lock().unwrap();
+            if *slot != mode {
+                *slot = mode;
+                crate::wake(&pair);
+            }
+        };
+        let seed = || set(wm::get_binding_mode());
+        wm::follow("binding_mode", &["mode"], seed, |event| {
+            set(event["change"].as_str().map(str::to_string));
+        });
+    });
+}
+
+pub const DEFAULT: &str = "default";
+
+pub const ICON: &str = "󰌌";
@@ -0,0 +1,136 @@
+//! Backlight level, read from and written to sysfs or through brightnessctl.
+
+use crate::BACKLIGHT_DEVICE;
+use crate::config::{BrightnessBackend, BrightnessConfig};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+const BACKLIGHTS: &str = "/sys/class/backlight";
+
+/// The sysfs directory of the backlight in use, found on first use and
+/// looked up again once reading it fails.
+static DEVICE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// `BACKLIGHT_DEVICE` if it exists, otherwise the first backlight listed.
+fn discover() -> Option<PathBuf> {
+    let preferred = Path::new(BACKLIGHTS).join(BACKLIGHT_DEVICE);
+    if preferred.exists() {
+        return Some(preferred);
+    }
+    let mut found: Vec<PathBuf> = fs::read_dir(BACKLIGHTS)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect();
+    found.sort();
+    found.into_iter().next()
+}
+
+/// The backlight's sysfs directory.
+pub fn device() -> Option<PathBuf> {
+    let mut cached = DEVICE.lock().unwrap();
+    if cached.is_none() {
+        *cached = discover();
+    }
+    cached.clone()
+}
+
+/// The backlight's name, e.g. `intel_backlight`.
+pub fn device_name() -> Option<String> {
+    Some(device()?.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Read a value of the backlight. Docking or undocking can swap which
+/// backlight exists, so a failed read forgets the device and tries once
+/// more with whichever is there now.
+fn read_value(file: &str) -> Result<u32, Box<dyn Error>> {
+    let read = |dir: &Path| -> Result<u32, Box<dyn Error>> {
+        Ok(fs::read_to_string(dir.join(file))?.trim().parse::<u32>()?)
+    };
+    let dir = device().ok_or("no backlight found")?;
+    match read(&dir) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            *DEVICE.lock().unwrap() = None;
+            match device() {
+                Some(fresh) if fresh != dir => read(&fresh),
+                _ => Err(e),
+            }
+        }
+    }
+}
+
+/// Get the current brightness level in percent, see [`percent`].
+pub fn get_brightness() -> Result<f32, Box<dyn Error>> {
+    let brightness = read_value("brightness")?;
+    let brightness_max = read_value("max_brightness")?;
+    percent(brightness, brightness_max).ok_or_else(|| "max_brightness is 0".into())
+}
+
+/// `brightness` as a percentage of `max`, left unrounded for the block's
+/// format to round, so full brightness never reads 99%. Clamped to 0..=100,
+/// as some drivers report a level above their maximum.
+fn percent(brightness: u32, max: u32) -> Option<f32> {
+    (max != 0).then(|| (brightness as f32 / max as f32 * 100.0).clamp(0.0, 100.0))
+}
+
+/// Move the brightness by `steps` configured steps (negative to dim),
+/// clamped to the device's range. Some backlights have only a handful of
+/// levels, so every step changes the level by at least one.
+pub fn adjust_brightness(cfg: &BrightnessConfig, steps: i32) -> Result<(), Box<dyn Error>> {
+    let current = read_value("brightness")? as i64;
+    let max = read_value("max_brightness")? as i64;
+    let delta = (max * cfg.step as i64 / 100).max(1) * steps as i64;
+    let target = (current + delta).clamp(0, max);
+    match cfg.backend {
+        BrightnessBackend::Brightnessctl => {
+            let name = device_name().ok_or("no backlight found")?;
+            crate::command::run(Command::new("brightnessctl").args([
+                "-q",
+                "-d",
+                &name,
+                "set",
+                &target.to_string(),
+            ]))?;
+        }
+        BrightnessBackend::Sysfs => {
+            let dir = device().ok_or("no backlight found")?;
+            fs::write(dir.join("brightness"), target.to_string())?
+        }
+    }
+    Ok(())
+}
+
+/// Built-in icons by level, dimmer below two thirds and one third.
+pub const RAMP: &[(f32, &str)] = &[(0.0, "󰃞"), (34.0, "󰃟"), (67.0, "󰃠")];
+
+#[cfg(test)]
+mod tests {
+    use super::percent;
+    use crate::config::NumberStyle;
+
+    #[test]
+    fn near_full_rounds_up_to_100() {
+        let shown = NumberStyle::default().format(percent(996, 1000).unwrap(), 0);
+        assert_eq!(shown, "100");
+    }
+
+    #[test]
+    fn full_with_a_small_maximum_is_100() {
+        assert_eq!(percent(7, 7), Some(100.0));
+    }
+
+    #[test]
+    fn zero_maximum_has_no_level() {
+        assert_eq!(percent(0, 0), None);
+        assert_eq!(percent(5, 0), None);
+    }
+
+    #[test]
+    fn level_above_maximum_is_clamped() {
+        assert_eq!(percent(1200, 1000), Some(100.0));
+    }
+}
@@ -0,0 +1,101 @@
+//! Idle inhibitor toggled by clicking the block.
+
+use crate::config::CaffeineConfig;
+use nix::sys::signal::{Signal, killpg};
+use nix::unistd::Pid;
+use std::env;
+use std::fs;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+/// Where the inhibitor's pid is kept while it runs, so a bar that was killed
+/// without stopping it can be cleaned up after by the next one.
+fn pid_file() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("rocketbar-caffeine.pid"),
+        _ => env::temp_dir().join("rocketbar-caffeine.pid"),
+    }
+}
+
+/// Whether the inhibitor is still running. Forgets it if it exited on its own.
+pub fn is_active(slot: &Mutex<Option<Child>>) -> bool {
+    let mut slot = slot.lock().unwrap();
+    if let Some(child) = slot.as_mut()
+        && !matches!(child.try_wait(), Ok(None))
+    {
+        *slot = None;
+    }
+    slot.is_some()
+}
+
+/// Start the inhibitor, or stop it when it is already running.
+///
+/// The command gets its own process group so stopping it also stops
+/// whatever it spawned, like the `sleep` held by `systemd-inhibit`.
+pub fn toggle(slot: &Mutex<Option<Child>>, cfg: &CaffeineConfig) -> io::Result<()> {
+    let mut slot = slot.lock().unwrap();
+    match slot.take() {
+        Some(child) => kill(child)?,
+        None => {
+            let child = Command::new("sh")
+                .arg("-c")
+                .arg(&cfg.command)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .process_group(0)
+                .spawn()?;
+            let _ = fs::write(pid_file(), child.id().to_string());
+            *slot = Some(child);
+        }
+    }
+    Ok(())
+}
+
+/// Stop the inhibitor if it is running, e.g. when the bar quits.
+pub fn stop(slot: &Mutex<Option<Child>>) {
+    if let Some(child) = slot.lock().unwrap().take() {
+        let _ = kill(child);
+    }
+}
+
+fn kill(mut child: Child) -> io::Result<()> {
+    let _ = killpg(Pid::from_raw(child.id() as i32), Signal::SIGTERM);
+    let _ = fs::remove_file(pid_file());
+    child.wait().map(drop)
+}
+
+/// Stop an inhibitor left running by a bar that was killed before it could
+/// stop it, which would otherwise keep the machine awake while the block
+/// shows caffeine as off. One whose parent is still a rocketbar belongs to a
+/// running bar and is left alone.
+pub fn reap_leftover() {
+    let path = pid_file();
+    let Some(pid) = fs::read_to_string(&path)
+        .ok()
+        .and_then(|pid| pid.trim().parse::<i32>().ok())
+    else {
+        return;
+    };
+    // `pid (comm) state ppid pgrp ...`; comm may itself contain spaces.
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).unwrap_or_default();
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map_or(Vec::new(), |(_, rest)| rest.split_whitespace().collect());
+    let (Some(ppid), Some(pgrp)) = (fields.get(1), fields.get(2)) else {
+        let _ = fs::remove_file(&path);
+        return;
+    };
+    let parent = fs::read_to_string(format!("/proc/{ppid}/comm")).unwrap_or_default();
+    if parent.trim() == "rocketbar" {
+        return;
+    }
+    if *pgrp == pid.to_string() {
+        let _ = killpg(Pid::from_raw(pid), Signal::SIGTERM);
+    }
+    let _ = fs::remove_file(&path);
+}
+
+pub const ICON: &str = "󰅶";
@@ -0,0 +1,92 @@
+//! The next calendar event, from the output of `khal list` or a command
+//! printing in the same shape.
+
+use super::run_shell_with_timeout;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// `09:30 Standup` or `09:30-10:00 Standup`.
+static EVENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{1,2}:\d{2})(?:\s*-\s*\d{1,2}:\d{2})?\s+(.+)$").unwrap());
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d{4})-(\d{1,2})-(\d{1,2})").unwrap());
+/// `16.10.2026`, or US style `10/16/2026`.
+static LOCAL_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d{1,2})([./])(\d{1,2})[./](\d{4})").unwrap());
+
+#[derive(Clone, PartialEq)]
+pub struct Event {
+    pub start: NaiveDateTime,
+    pub title: String,
+}
+
+/// Run `command` and read the timed events it lists, in order.
+pub fn get_events(command: &str, today: NaiveDate) -> Option<Vec<Event>> {
+    let stdout = run_shell_with_timeout(command, crate::COMMAND_TIMEOUT)?;
+    Some(parse_events(&stdout, today))
+}
+
+/// khal prints a heading per day, like `Today, 16.10.2026` or `Tomorrow,
+/// ...`, followed by that day's events. All-day events have no time and are
+/// skipped.
+fn parse_events(stdout: &str, today: NaiveDate) -> Vec<Event> {
+    let mut day = today;
+    let mut events = Vec::new();
+    for line in stdout.lines().map(str::trim) {
+        if let Some(caps) = EVENT.captures(line) {
+            if let Ok(time) = NaiveTime::parse_from_str(&caps[1], "%H:%M") {
+                events.push(Event {
+                    start: day.and_time(time),
+                    title: caps[2].trim().to_string(),
+                });
+            }
+        } else if let Some(heading) = parse_heading(line, today) {
+            day = heading;
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    events
+}
+
+fn parse_heading(line: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if line.starts_with("Today") {
+        return Some(today);
+    }
+    if line.starts_with("Tomorrow") {
+        return Some(today + Duration::days(1));
+    }
+    let number = |s: &str| s.parse::<u32>().ok();
+    if let Some(caps) = ISO_DATE.captures(line) {
+        return NaiveDate::from_ymd_opt(
+            caps[1].parse().ok()?,
+            number(&caps[2])?,
+            number(&caps[3])?,
+        );
+    }
+    let caps = LOCAL_DATE.captures(line)?;
+    let (first, second) = (number(&caps[1])?, number(&caps[3])?);
+    let (day, month) = if &caps[2] == "/" {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    NaiveDate::from_ymd_opt(caps[4].parse().ok()?, month, day)
+}
+
+/// The first event starting after `now`.
+pub fn next(events: &[Event], now: NaiveDateTime) -> Option<&Event> {
+    events.iter().find(|e| e.start > now)
+}
+
+/// Time left as `25m` or `1h05m`.
+pub fn countdown(left: Duration) -> String {
+    let minutes = (left.num_seconds() + 59) / 60;
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+pub const ICON: &str = "";
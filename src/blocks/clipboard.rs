@@ -0,0 +1,114 @@
+//! Whether the clipboard holds anything, and how much, through `wl-paste` on
+//! Wayland or `xclip` on X11.
+
+use super::run_shell_with_timeout;
+use crate::config::SharedConfig;
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often a running `wl-paste --watch` checks whether a reload disabled
+/// the block.
+const RELOAD_CHECK: Duration = Duration::from_secs(1);
+
+fn wayland() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Bytes on the clipboard, 0 when it is empty. Returns `None` when there is
+/// no display to ask.
+pub fn get_size() -> Option<usize> {
+    // The paste tools fail on an empty clipboard, which still counts 0.
+    let command = if wayland() {
+        "wl-paste --no-newline 2>/dev/null | wc -c"
+    } else if env::var_os("DISPLAY").is_some() {
+        "xclip -o -selection clipboard 2>/dev/null | wc -c"
+    } else {
+        return None;
+    };
+    run_shell_with_timeout(command, TIMEOUT)?.parse().ok()
+}
+
+/// Keep `slot` up to date, waking the render loop on changes. On Wayland
+/// `wl-paste --watch` reports every change as it happens, much like the
+/// pactl thread does for volume; elsewhere, or if it exits, the clipboard
+/// is polled every `clipboard.interval` seconds. Nothing runs while the
+/// block is disabled, and a reload that disables it stops `wl-paste`.
+pub fn spawn_watcher(
+    slot: Arc<Mutex<Option<usize>>>,
+    config: SharedConfig,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    thread::spawn(move || {
+        let mut watch = wayland();
+        loop {
+            let cfg = config.read().unwrap().clipboard.clone();
+            if !cfg.enabled {
+                // Try the watcher again once the block is turned back on.
+                watch = wayland();
+                thread::sleep(Duration::from_secs(cfg.interval.max(1)));
+                continue;
+            }
+            if watch {
+                watch = false;
+                watch_changes(&slot, &config, &pair);
+                continue;
+            }
+            update(&slot, &pair);
+            thread::sleep(Duration::from_secs(cfg.interval.max(1)));
+        }
+    });
+}
+
+/// Run `wl-paste --watch` until it exits or the block is disabled.
+fn watch_changes(
+    slot: &Arc<Mutex<Option<usize>>>,
+    config: &SharedConfig,
+    pair: &Arc<(Mutex<bool>, Condvar)>,
+) {
+    let child = Command::new("wl-paste")
+        .args(["--watch", "echo"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return;
+    };
+    if let Some(stdout) = child.stdout.take() {
+        let (slot, pair) = (Arc::clone(slot), Arc::clone(pair));
+        thread::spawn(move || {
+            // wl-paste runs `echo` once right away, then per change.
+            for _ in BufReader::new(stdout).lines().map_while(Result::ok) {
+                update(&slot, &pair);
+            }
+        });
+    }
+    loop {
+        thread::sleep(RELOAD_CHECK);
+        if !matches!(child.try_wait(), Ok(None)) {
+            return;
+        }
+        if !config.read().unwrap().clipboard.enabled {
+            let _ = child.kill();
+            let _ = child.wait();
+            return;
+        }
+    }
+}
+
+fn update(slot: &Mutex<Option<usize>>, pair: &(Mutex<bool>, Condvar)) {
+    let size = get_size();
+    let mut lock = slot.lock().unwrap();
+    if *lock != size {
+        *lock = size;
+        crate::wake(pair);
+    }
+}
+
+pub const ICON: &str = "";
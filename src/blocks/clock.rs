@@ -0,0 +1,42 @@
+//! Clock, optionally rotating through several formats.
+
+use crate::config::ClockConfig;
+use chrono::{DateTime, Local, Timelike};
+use std::time::{Duration, Instant};
+
+/// Whether a click `clicked` at still has the clock show `click_format`.
+pub fn showing_click_format(cfg: &ClockConfig, clicked: Option<Instant>) -> bool {
+    cfg.click_format.is_some()
+        && clicked.is_some_and(|at| at.elapsed() < Duration::from_secs(cfg.click_seconds))
+}
+
+/// Time from `now` until the next minute starts.
+pub fn until_next_minute(now: &DateTime<Local>) -> Duration {
+    let into_minute =
+        Duration::from_secs(now.second().into()) + Duration::from_nanos(now.nanosecond().into());
+    Duration::from_secs(60).saturating_sub(into_minute)
+}
+
+pub const ICON: &str = "󰥔";
+
+/// Format `now` with the clock format for this tick, advancing to the next
+/// format every `rotate_every` ticks, or with `click_format` for a while after
+/// the clock was `clicked`.
+pub fn format_clock(
+    now: &DateTime<Local>,
+    cfg: &ClockConfig,
+    tick: u64,
+    clicked: Option<Instant>,
+) -> String {
+    if let Some(format) = &cfg.click_format
+        && showing_click_format(cfg, clicked)
+    {
+        return now.format(&format.0).to_string();
+    }
+    let Some(first) = cfg.formats.first() else {
+        return String::new();
+    };
+    let index = (tick / cfg.rotate_every.max(1)) as usize % cfg.formats.len();
+    let format = cfg.formats.get(index).unwrap_or(first);
+    now.format(&format.0).to_string()
+}
@@ -0,0 +1,37 @@
+//! Online indicator: a default route plus a TCP connect to a known host,
+//! and the time that connect takes.
+
+use crate::config::{ConnectivityConfig, LatencyConfig};
+use crate::route;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// Whether the machine looks online. Without a default route this is false
+/// straight away; otherwise the configured target must accept a TCP connection.
+pub fn is_online(cfg: &ConnectivityConfig) -> bool {
+    if route::default_route().is_none() {
+        return false;
+    }
+    let Some(target) = &cfg.target else {
+        return true;
+    };
+    connect(target, cfg.timeout_ms).is_some()
+}
+
+/// Milliseconds a TCP connection to the configured target took, or `None`
+/// when it failed or timed out.
+pub fn latency(cfg: &LatencyConfig) -> Option<f32> {
+    connect(&cfg.target, cfg.timeout_ms).map(|time| time.as_secs_f32() * 1000.0)
+}
+
+/// Open a TCP connection to `target` and return how long it took. Name
+/// resolution is not counted.
+fn connect(target: &str, timeout_ms: u64) -> Option<Duration> {
+    let addr = target.to_socket_addrs().ok()?.next()?;
+    let start = Instant::now();
+    TcpStream::connect_timeout(&addr, Duration::from_millis(timeout_ms)).ok()?;
+    Some(start.elapsed())
+}
+
+pub const ICON: &str = "●";
+pub const LATENCY_ICON: &str = "󰨮";
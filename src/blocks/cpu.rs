@@ -0,0 +1,59 @@
+//! CPU usage, sampled on a poller thread at a steady cadence so the reading
+//! does not depend on how often the bar happens to redraw.
+
+use std::collections::VecDeque;
+use std::thread;
+use sysinfo::{MINIMUM_CPU_UPDATE_INTERVAL, System};
+
+/// Usage in percent, overall and per core.
+#[derive(Clone, PartialEq)]
+pub struct CpuUsage {
+    pub global: f32,
+    pub cores: Vec<f32>,
+}
+
+/// The most recent samples, averaged to smooth out short spikes.
+pub struct Sampler {
+    sys: System,
+    history: VecDeque<CpuUsage>,
+}
+
+impl Sampler {
+    /// Take a first reading to measure against. sysinfo computes usage
+    /// between two refreshes, so this waits long enough for the next sample
+    /// to be meaningful rather than reporting 0%.
+    pub fn new() -> Self {
+        let mut sys = System::new();
+        sys.refresh_cpu_all();
+        thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+        Self {
+            sys,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Refresh and return the average of the last `samples` readings.
+    pub fn sample(&mut self, samples: usize) -> CpuUsage {
+        self.sys.refresh_cpu_all();
+        self.history.push_back(CpuUsage {
+            global: self.sys.global_cpu_usage(),
+            cores: self.sys.cpus().iter().map(|c| c.cpu_usage()).collect(),
+        });
+        while self.history.len() > samples.max(1) {
+            self.history.pop_front();
+        }
+        let n = self.history.len() as f32;
+        let cores = self.history.back().map_or(0, |u| u.cores.len());
+        CpuUsage {
+            global: self.history.iter().map(|u| u.global).sum::<f32>() / n,
+            cores: (0..cores)
+                .map(|i| {
+                    let sum: f32 = self.history.iter().filter_map(|u| u.cores.get(i)).sum();
+                    sum / n
+                })
+                .collect(),
+        }
+    }
+}
+
+pub const ICON: &str = "";
@@ -0,0 +1,19 @@
+//! Running container count for docker or podman.
+
+use super::run_shell;
+use crate::config::DockerConfig;
+use std::path::Path;
+
+/// Count running containers by counting the output lines of the configured
+/// command. Returns `None` when the socket or the binary is missing.
+pub fn get_container_count(cfg: &DockerConfig) -> Option<u32> {
+    if let Some(socket) = &cfg.socket
+        && !Path::new(socket).exists()
+    {
+        return None;
+    }
+    let stdout = run_shell(&cfg.command)?;
+    Some(stdout.lines().filter(|l| !l.trim().is_empty()).count() as u32)
+}
+
+pub const ICON: &str = "";
@@ -0,0 +1,109 @@
+//! Screen color temperature from a running gammastep, which a click can
+//! toggle on and off.
+
+use crate::command;
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+use std::error::Error;
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The daemon last toggled off from the bar, if it still is. gammastep has
+/// no way to ask whether it is toggled off, and `gammastep -p` prints what
+/// it would set either way, so the bar keeps track of its own toggles.
+static DISABLED: Mutex<Option<Pid>> = Mutex::new(None);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Period {
+    Day,
+    Night,
+    Transition,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct State {
+    /// Kelvin.
+    pub temperature: u32,
+    pub period: Period,
+}
+
+/// The pid of the running gammastep daemon, found by its process name.
+fn find_daemon() -> Option<Pid> {
+    fs::read_dir("/proc")
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<i32>().ok())
+        .find(|pid| {
+            fs::read_to_string(format!("/proc/{pid}/comm"))
+                .is_ok_and(|comm| comm.trim() == "gammastep")
+        })
+        .map(Pid::from_raw)
+}
+
+/// Ask gammastep what it is setting right now. Returns `None` when the
+/// daemon is not running, without starting one.
+pub fn get_state() -> Option<State> {
+    let pid = find_daemon()?;
+    // A restarted daemon starts out on.
+    let mut disabled = DISABLED.lock().unwrap();
+    if disabled.is_some_and(|off| off != pid) {
+        *disabled = None;
+    }
+    drop(disabled);
+    let stdout =
+        command::run_with_timeout(Command::new("gammastep").arg("-p"), Duration::from_secs(5))
+            .ok()?;
+    parse_print(&stdout)
+}
+
+/// `gammastep -p` prints lines such as `Period: Transition (43.21% day)`
+/// and `Color temperature: 4500K`.
+fn parse_print(stdout: &str) -> Option<State> {
+    let field = |name: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    let temperature = field("Color temperature")?
+        .strip_suffix('K')?
+        .parse()
+        .ok()?;
+    let period = match field("Period")? {
+        "Daytime" => Period::Day,
+        "Night" => Period::Night,
+        _ => Period::Transition,
+    };
+    Some(State {
+        temperature,
+        period,
+    })
+}
+
+/// Toggle the running daemon, which gammastep does on `SIGUSR1`.
+pub fn toggle() -> Result<(), Box<dyn Error>> {
+    let pid = find_daemon().ok_or("gammastep is not running")?;
+    kill(pid, Signal::SIGUSR1)?;
+    let mut disabled = DISABLED.lock().unwrap();
+    *disabled = if *disabled == Some(pid) {
+        None
+    } else {
+        Some(pid)
+    };
+    Ok(())
+}
+
+/// Whether the daemon was toggled off from the bar.
+pub fn is_off() -> bool {
+    DISABLED.lock().unwrap().is_some()
+}
+
+pub fn icon(period: Period) -> &'static str {
+    match period {
+        Period::Day => "󰖙",
+        Period::Night => "󰖔",
+        Period::Transition => "󰖛",
+    }
+}
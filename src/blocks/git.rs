@@ -0,0 +1,67 @@
+//! Branch and dirty state of watched git repositories.
+
+use crate::config::expand_home;
+use std::process::Command;
+
+/// Summary of `git status` for one repository.
+#[derive(Clone, PartialEq)]
+pub struct GitStatus {
+    pub path: String,
+    pub branch: String,
+    pub dirty: bool,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+/// Read the status of `path`, or `None` when it is not a git repository.
+pub fn get_git_status(path: &str) -> Option<GitStatus> {
+    let stdout = crate::command::run_with_timeout(
+        Command::new("git").arg("-C").arg(expand_home(path)).args([
+            "status",
+            "--porcelain=v2",
+            "--branch",
+        ]),
+        crate::COMMAND_TIMEOUT,
+    )
+    .ok()?;
+    let mut status = GitStatus {
+        path: path.to_string(),
+        branch: String::new(),
+        dirty: false,
+        ahead: 0,
+        behind: 0,
+    };
+    for line in stdout.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            status.branch = head.to_string();
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            for count in ab.split_whitespace() {
+                if let Some(n) = count.strip_prefix('+') {
+                    status.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = count.strip_prefix('-') {
+                    status.behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if !line.starts_with('#') {
+            status.dirty = true;
+        }
+    }
+    Some(status)
+}
+
+pub const ICON: &str = "";
+
+/// Format a repository status like `main* ↑1 ↓2`.
+pub fn format_git(status: &GitStatus) -> String {
+    let mut text = status.branch.clone();
+    if status.dirty {
+        text.push('*');
+    }
+    if status.ahead > 0 {
+        text.push_str(&format!(" ↑{}", status.ahead));
+    }
+    if status.behind > 0 {
+        text.push_str(&format!(" ↓{}", status.behind));
+    }
+    text
+}
@@ -0,0 +1,34 @@
+//! Count of recent journal entries at or above a priority, e.g. the errors
+//! of the last five minutes.
+
+use crate::command;
+use crate::config::JournalConfig;
+use std::process::Command;
+use std::time::Duration;
+
+/// Journal queries over a large window can take a while.
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Entries logged within the last `window` seconds at `priority` or worse.
+pub fn get_count(cfg: &JournalConfig) -> Option<u32> {
+    let stdout = command::run_with_timeout(
+        Command::new("journalctl").args([
+            "--priority",
+            &cfg.priority,
+            "--since",
+            &format!("-{}s", cfg.window),
+            "--no-pager",
+            "--quiet",
+            // One line per entry, however many lines its message has.
+            "--output",
+            "json",
+            "--output-fields",
+            "PRIORITY",
+        ]),
+        TIMEOUT,
+    )
+    .ok()?;
+    Some(stdout.lines().count() as u32)
+}
+
+pub const ICON: &str = "󰼺";
@@ -0,0 +1,69 @@
+//! Keyboard backlight level, from the first `*kbd_backlight` LED in sysfs.
+
+use crate::config::{BrightnessBackend, BrightnessConfig};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const ICON: &str = "";
+
+/// The sysfs directory of the keyboard backlight LED, if there is one.
+pub fn find_device() -> Option<PathBuf> {
+    let mut leds: Vec<PathBuf> = fs::read_dir("/sys/class/leds")
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .ends_with("kbd_backlight")
+        })
+        .map(|entry| entry.path())
+        .collect();
+    leds.sort();
+    leds.into_iter().next()
+}
+
+fn read_value(device: &Path, file: &str) -> Result<u32, Box<dyn Error>> {
+    Ok(fs::read_to_string(device.join(file))?
+        .trim()
+        .parse::<u32>()?)
+}
+
+/// The LED name and its level as a percentage of `max_brightness`.
+pub fn get_level() -> Option<(String, f32)> {
+    let device = find_device()?;
+    let level = read_value(&device, "brightness").ok()?;
+    let max = read_value(&device, "max_brightness").ok()?;
+    if max == 0 {
+        return None;
+    }
+    let name = device.file_name()?.to_string_lossy().into_owned();
+    Some((name, (level as f32 * 100.0 / max as f32).clamp(0.0, 100.0)))
+}
+
+/// Move the level by `steps` configured steps (negative to dim), clamped to
+/// the LED's range. Keyboard backlights often have only a few levels, so
+/// every step changes the level by at least one.
+pub fn adjust(cfg: &BrightnessConfig, steps: i32) -> Result<(), Box<dyn Error>> {
+    let device = find_device().ok_or("no keyboard backlight found")?;
+    let current = read_value(&device, "brightness")? as i64;
+    let max = read_value(&device, "max_brightness")? as i64;
+    let delta = (max * cfg.step as i64 / 100).max(1) * steps as i64;
+    let target = (current + delta).clamp(0, max);
+    match cfg.backend {
+        BrightnessBackend::Brightnessctl => {
+            let name = device.file_name().unwrap_or_default().to_string_lossy();
+            crate::command::run(Command::new("brightnessctl").args([
+                "-q",
+                "-d",
+                &name,
+                "set",
+                &target.to_string(),
+            ]))?;
+        }
+        BrightnessBackend::Sysfs => fs::write(device.join("brightness"), target.to_string())?,
+    }
+    Ok(())
+}
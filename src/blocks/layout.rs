@@ -0,0 +1,55 @@
+//! Layout of the focused container under sway or i3.
+
+use crate::config::SharedConfig;
+use crate::wm;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn current_layout() -> Option<String> {
+    let tree = wm::get_tree()?;
+    let container = wm::find_focused_container(&tree)?;
+    container["layout"].as_str().map(str::to_string)
+}
+
+/// Re-read the layout whenever focus, workspaces or bindings change, since
+/// no event reports layout changes directly. Does nothing without a window
+/// manager; waits for the block to be enabled before subscribing.
+pub fn spawn_subscriber(
+    slot: Arc<Mutex<Option<String>>>,
+    config: SharedConfig,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    if !wm::available() {
+        return;
+    }
+    thread::spawn(move || {
+        while !config.read().unwrap().layout.enabled {
+            thread::sleep(Duration::from_secs(5));
+        }
+        let update = || {
+            let layout = current_layout();
+            let mut slot = slot.lock().unwrap();
+            if *slot != layout {
+                *slot = layout;
+                crate::wake(&pair);
+            }
+        };
+        wm::follow(
+            "layout",
+            &["window", "workspace", "binding"],
+            update,
+            |_| update(),
+        );
+    });
+}
+
+pub fn icon(layout: &str) -> &'static str {
+    match layout {
+        "splith" => "󰯌",
+        "splitv" => "󰯋",
+        "tabbed" => "󰓩",
+        "stacked" => "󰌨",
+        _ => "󰰟",
+    }
+}
@@ -0,0 +1,23 @@
+//! Unread mail count from local maildirs.
+
+use crate::config::{MailConfig, expand_home};
+use std::fs;
+
+/// Count the messages in the `new/` directory of every configured maildir.
+/// Returns `None` when none of the maildirs can be read.
+pub fn get_unread_count(cfg: &MailConfig) -> Option<u32> {
+    let mut total = None;
+    for maildir in &cfg.maildirs {
+        let new = expand_home(maildir).join("new");
+        if let Ok(entries) = fs::read_dir(new) {
+            let count = entries
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+                .count() as u32;
+            *total.get_or_insert(0) += count;
+        }
+    }
+    total
+}
+
+pub const ICON: &str = "";
@@ -0,0 +1,39 @@
+//! Memory split into used, buffers/cache and free, from `/proc/meminfo`.
+
+use std::fs;
+
+/// Sizes in kB.
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    /// Buffers and page cache, which the kernel gives back under pressure.
+    pub cache: u64,
+}
+
+impl MemInfo {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free + self.cache)
+    }
+}
+
+pub fn get_meminfo() -> Option<MemInfo> {
+    parse_meminfo(&fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+/// Lines look like `MemTotal:       16310572 kB`.
+fn parse_meminfo(data: &str) -> Option<MemInfo> {
+    let field = |name: &str| {
+        data.lines().find_map(|line| {
+            let value = line.strip_prefix(name)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let total = field("MemTotal").filter(|&total| total > 0)?;
+    Some(MemInfo {
+        total,
+        free: field("MemFree")?,
+        cache: field("Buffers")? + field("Cached")?,
+    })
+}
+
+pub const ICON: &str = "";
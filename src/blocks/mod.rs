@@ -0,0 +1,115 @@
+//! Optional blocks that are enabled from the config file.
+
+pub mod battery;
+pub mod binding_mode;
+pub mod brightness;
+pub mod caffeine;
+pub mod calendar;
+pub mod clipboard;
+pub mod clock;
+pub mod connectivity;
+pub mod cpu;
+pub mod docker;
+pub mod gammastep;
+pub mod git;
+pub mod journal;
+pub mod kbd_backlight;
+pub mod layout;
+pub mod mail;
+pub mod memory;
+pub mod nvme;
+pub mod pipewire;
+pub mod power_profile;
+pub mod public_ip;
+pub mod raid;
+pub mod recording;
+pub mod script;
+pub mod sessions;
+pub mod smart;
+pub mod sun;
+pub mod swap;
+pub mod tasks;
+pub mod temperature;
+pub mod timer;
+pub mod window;
+pub mod wireguard;
+pub mod zfs;
+
+use crate::config::{Format, NumberStyle};
+use std::process::Command;
+use std::time::Duration;
+
+/// Color for a value on a warning/critical ramp, or `None` below warning.
+pub fn threshold_color(value: f32, warning: f32, critical: f32) -> Option<&'static str> {
+    if value >= critical {
+        Some(crate::RED)
+    } else if value >= warning {
+        Some(crate::YELLOW)
+    } else {
+        None
+    }
+}
+
+/// Pick the icon of the last step of `ramp`, given in ascending order, that
+/// `value` reaches (the first below all of them), and format the value as a
+/// percentage.
+pub fn render_level<'a>(
+    value: f32,
+    ramp: &[(f32, &'a str)],
+    format: &Format,
+    numbers: &NumberStyle,
+) -> (&'a str, String) {
+    let icon = ramp
+        .iter()
+        .rev()
+        .find(|(from, _)| value >= *from)
+        .or(ramp.first())
+        .map_or("", |(_, icon)| icon);
+    (icon, format.apply(value, "%", numbers))
+}
+
+/// Draw a bar `width` cells wide out of segments given as a fraction of the
+/// whole, a glyph and a color. Returns the plain bar and the bar with each
+/// segment in a pango span of its color.
+pub fn render_bar(segments: &[(f32, char, &str)], width: usize) -> (String, String) {
+    let mut text = String::new();
+    let mut markup = String::new();
+    let mut drawn = 0;
+    let mut total = 0.0;
+    for &(fraction, glyph, color) in segments {
+        // Round where each segment ends rather than each length, so the
+        // cells always add up to the width.
+        total += fraction.max(0.0);
+        let end = ((total * width as f32).round() as usize).min(width);
+        let cells = glyph.to_string().repeat(end.saturating_sub(drawn));
+        if !cells.is_empty() {
+            markup.push_str(&format!("<span foreground=\"{color}\">{cells}</span>"));
+        }
+        text.push_str(&cells);
+        drawn = drawn.max(end);
+    }
+    (text, markup)
+}
+
+/// Run `command` through `sh -c`, returning its trimmed stdout when it exits
+/// successfully within [`crate::COMMAND_TIMEOUT`].
+pub fn run_shell(command: &str) -> Option<String> {
+    run_shell_with_timeout(command, crate::COMMAND_TIMEOUT)
+}
+
+/// Like [`run_shell`], but kill the command once it runs past `timeout`.
+pub fn run_shell_with_timeout(command: &str, timeout: Duration) -> Option<String> {
+    crate::command::run_with_timeout(Command::new("sh").arg("-c").arg(command), timeout).ok()
+}
+
+/// Fetch `url` with curl, giving up after `timeout` seconds. Returns the body
+/// on a successful (2xx) response.
+pub fn http_get(url: &str, timeout: u64) -> Option<String> {
+    // curl gives up on its own; the extra second only catches a curl that
+    // hangs anyway.
+    crate::command::run_with_timeout(
+        Command::new("curl").args(["-fsS", "--max-time", &timeout.to_string(), url]),
+        Duration::from_secs(timeout + 1),
+    )
+    .ok()
+}
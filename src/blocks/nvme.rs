@@ -0,0 +1,22 @@
+//! NVMe drive temperature from hwmon.
+
+use std::fs;
+
+/// Hottest NVMe drive in degrees Celsius, found by looking for hwmon devices
+/// named `nvme`. Returns `None` when there is no such device.
+pub fn get_nvme_temperature() -> Option<f32> {
+    fs::read_dir("/sys/class/hwmon")
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|dir| fs::read_to_string(dir.join("name")).is_ok_and(|name| name.trim() == "nvme"))
+        .filter_map(|dir| crate::read_int_from_file(dir.join("temp1_input").to_str()?).ok())
+        .map(|millis| millis as f32 / 1000.0)
+        .reduce(f32::max)
+}
+
+/// Default warning and critical thresholds, in degrees Celsius; most
+/// drives throttle around the critical one.
+pub const THRESHOLDS: (f32, f32) = (60.0, 70.0);
+
+pub const ICON: &str = "󰋊";
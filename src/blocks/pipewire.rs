@@ -0,0 +1,62 @@
+//! PipeWire graph sample rate and quantum from `pw-metadata`.
+
+use crate::command;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Graph {
+    /// Samples per second.
+    pub rate: u32,
+    /// Samples per processing cycle, which sets the latency.
+    pub quantum: u32,
+}
+
+impl Graph {
+    /// E.g. `48kHz/256` or `44.1kHz/1024`.
+    pub fn describe(&self) -> String {
+        let khz = format!("{:.1}", self.rate as f32 / 1000.0);
+        let khz = khz.strip_suffix(".0").unwrap_or(&khz);
+        format!("{khz}kHz/{}", self.quantum)
+    }
+}
+
+/// Read the graph settings. Returns `None` without running anything when no
+/// PipeWire server socket exists, so machines without PipeWire stay quiet.
+pub fn get_graph() -> Option<Graph> {
+    let runtime = env::var_os("XDG_RUNTIME_DIR")?;
+    if !Path::new(&runtime).join("pipewire-0").exists() {
+        return None;
+    }
+    let stdout = command::run_with_timeout(
+        Command::new("pw-metadata").args(["-n", "settings"]),
+        crate::COMMAND_TIMEOUT,
+    )
+    .ok()?;
+    parse_metadata(&stdout)
+}
+
+/// Lines look like `update: id:0 key:'clock.rate' value:'48000' type:''`.
+/// A nonzero `clock.force-*` value, set to pin the graph, overrides the
+/// default one.
+fn parse_metadata(stdout: &str) -> Option<Graph> {
+    let value = |key: &str| {
+        let needle = format!("key:'{key}' value:'");
+        stdout.lines().find_map(|line| {
+            let rest = &line[line.find(&needle)? + needle.len()..];
+            rest[..rest.find('\'')?].parse::<u32>().ok()
+        })
+    };
+    let setting = |key: &str| {
+        value(&format!("clock.force-{key}"))
+            .filter(|&forced| forced > 0)
+            .or_else(|| value(&format!("clock.{key}")))
+    };
+    Some(Graph {
+        rate: setting("rate")?,
+        quantum: setting("quantum")?,
+    })
+}
+
+pub const ICON: &str = "󱑽";
@@ -0,0 +1,64 @@
+//! The active power-profiles-daemon profile, or the CPU frequency governor
+//! where the daemon is not installed.
+
+use crate::command;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+const GOVERNOR: &str = "/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Whether `program` is found in `$PATH`, so a missing daemon is not
+/// reported as a failing command on every poll.
+fn installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// The profile name, e.g. `balanced`, or the governor, e.g. `powersave`.
+pub fn get_profile() -> Option<String> {
+    if installed("powerprofilesctl") {
+        return command::run_with_timeout(Command::new("powerprofilesctl").arg("get"), TIMEOUT)
+            .ok();
+    }
+    Some(fs::read_to_string(GOVERNOR).ok()?.trim().to_string())
+}
+
+/// Switch to the daemon's next profile, wrapping around. `powerprofilesctl
+/// list` prints each profile as a `name:` line, the active one marked `*`.
+pub fn cycle() -> Result<(), Box<dyn Error>> {
+    let list = command::run_with_timeout(Command::new("powerprofilesctl").arg("list"), TIMEOUT)?;
+    let profiles: Vec<(bool, &str)> = list
+        .lines()
+        .filter_map(|line| {
+            let (active, line) = match line.trim().strip_prefix('*') {
+                Some(rest) => (true, rest.trim()),
+                None => (false, line.trim()),
+            };
+            let name = line.strip_suffix(':')?;
+            (!name.contains(' ')).then_some((active, name))
+        })
+        .collect();
+    // The list runs from performance down; cycle towards power saving.
+    let current = profiles.iter().position(|(active, _)| *active).unwrap_or(0);
+    let (_, next) = profiles
+        .get((current + 1) % profiles.len().max(1))
+        .ok_or("no power profiles listed")?;
+    command::run_with_timeout(
+        Command::new("powerprofilesctl").args(["set", next]),
+        TIMEOUT,
+    )?;
+    Ok(())
+}
+
+/// A leaf when saving power, a gauge when going fast, scales in between.
+pub fn icon(profile: &str) -> &'static str {
+    match profile {
+        "power-saver" | "powersave" => "󰌪",
+        "performance" => "󰓅",
+        _ => "󰗑",
+    }
+}
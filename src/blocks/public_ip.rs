@@ -0,0 +1,28 @@
+//! Public (egress) address, as reported by an external lookup service.
+
+use super::http_get;
+use crate::config::PublicIpConfig;
+use std::net::IpAddr;
+
+/// Query every configured lookup URL and collect the addresses they report.
+/// `None` when no lookup succeeded, so the caller can keep its last value.
+pub fn get_public_ip(cfg: &PublicIpConfig) -> Option<Vec<IpAddr>> {
+    let addrs: Vec<IpAddr> = cfg
+        .urls
+        .iter()
+        .filter_map(|url| http_get(url, cfg.timeout))
+        .filter_map(|body| body.trim().parse().ok())
+        .collect();
+    (!addrs.is_empty()).then_some(addrs)
+}
+
+/// Whether any address falls outside the expected VPN ranges. Always false
+/// when no ranges are configured.
+pub fn is_leaking(addrs: &[IpAddr], cfg: &PublicIpConfig) -> bool {
+    !cfg.vpn_ranges.is_empty()
+        && addrs
+            .iter()
+            .any(|addr| !cfg.vpn_ranges.iter().any(|range| range.contains(*addr)))
+}
+
+pub const ICON: &str = "";
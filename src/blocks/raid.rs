@@ -0,0 +1,70 @@
+//! Software RAID health from `/proc/mdstat`.
+
+use std::fs;
+
+/// One md array and how it is doing.
+pub struct Array {
+    pub name: String,
+    /// Member status as printed by the kernel, e.g. `UU` or `U_`.
+    pub members: String,
+    /// Percentage done of a running recovery, resync, check or reshape.
+    pub progress: Option<f32>,
+}
+
+impl Array {
+    /// Whether every member is up and nothing is being rebuilt.
+    pub fn is_clean(&self) -> bool {
+        !self.members.contains('_') && self.progress.is_none()
+    }
+}
+
+/// Read every md array. Returns an empty list when there are none or the
+/// md driver is not loaded.
+pub fn get_arrays() -> Vec<Array> {
+    fs::read_to_string("/proc/mdstat")
+        .map(|data| parse_mdstat(&data))
+        .unwrap_or_default()
+}
+
+/// Each array starts with an unindented `md0 : active raid1 ...` line,
+/// followed by indented lines holding the `[UU]` member status and, while
+/// the array is rebuilt, a `recovery =  8.5% (...)` progress line.
+fn parse_mdstat(data: &str) -> Vec<Array> {
+    let mut arrays: Vec<Array> = Vec::new();
+    for line in data.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            if let Some((name, _)) = line.split_once(" : ")
+                && name.starts_with("md")
+            {
+                arrays.push(Array {
+                    name: name.trim().to_string(),
+                    members: String::new(),
+                    progress: None,
+                });
+            }
+            continue;
+        }
+        let Some(array) = arrays.last_mut() else {
+            continue;
+        };
+        if let Some(members) = line
+            .split_whitespace()
+            .rev()
+            .find_map(|word| word.strip_prefix('[')?.strip_suffix(']'))
+            .filter(|m| !m.is_empty() && m.chars().all(|c| c == 'U' || c == '_'))
+        {
+            array.members = members.to_string();
+        }
+        for op in ["recovery", "resync", "check", "reshape"] {
+            if let Some((_, rest)) = line.split_once(&format!("{op} ="))
+                && let Some(percent) = rest.split_whitespace().next()
+                && let Ok(percent) = percent.trim_end_matches('%').parse()
+            {
+                array.progress = Some(percent);
+            }
+        }
+    }
+    arrays
+}
+
+pub const ICON: &str = "";
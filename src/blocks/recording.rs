@@ -0,0 +1,16 @@
+//! Screen recording indicator, lit while a recorder process is running.
+
+use crate::config::RecordingConfig;
+use sysinfo::{ProcessesToUpdate, System};
+
+/// Check whether any of the configured recorder processes is running.
+pub fn is_recording(sys: &mut System, cfg: &RecordingConfig) -> bool {
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    sys.processes().values().any(|process| {
+        cfg.processes
+            .iter()
+            .any(|name| process.name() == name.as_str())
+    })
+}
+
+pub const ICON: &str = "󰑊";
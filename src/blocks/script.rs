@@ -0,0 +1,75 @@
+//! User-defined blocks showing the output of a shell command, each refreshed
+//! on its own schedule.
+
+use crate::cache::BlockCache;
+use crate::command;
+use crate::config::SharedConfig;
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Keep one thread running per configured script. Scripts added by a config
+/// reload get a thread within a second; a thread exits once its script is
+/// removed from the config.
+pub fn spawn_scripts(
+    config: SharedConfig,
+    outputs: Arc<BlockCache>,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    let running = Arc::new(Mutex::new(HashSet::new()));
+    thread::spawn(move || {
+        loop {
+            let names: Vec<String> = config.read().unwrap().scripts.keys().cloned().collect();
+            for name in names {
+                if running.lock().unwrap().insert(name.clone()) {
+                    let config = Arc::clone(&config);
+                    let outputs = Arc::clone(&outputs);
+                    let pair = Arc::clone(&pair);
+                    let running = Arc::clone(&running);
+                    thread::spawn(move || {
+                        run_script(&name, config, outputs, pair);
+                        running.lock().unwrap().remove(&name);
+                    });
+                }
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    });
+}
+
+/// Run one script every `interval` seconds until it disappears from the config.
+fn run_script(
+    name: &str,
+    config: SharedConfig,
+    outputs: Arc<BlockCache>,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    loop {
+        let Some(script) = config.read().unwrap().scripts.get(name).cloned() else {
+            if outputs.remove(name) {
+                crate::wake(&pair);
+            }
+            return;
+        };
+        // Like i3blocks, only the first line is shown. A failing command
+        // keeps the last value around so it can go stale, as does one killed
+        // for running too long; empty output hides the block.
+        let timeout = Duration::from_secs(script.timeout.max(1));
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(&script.command);
+        command::keep_locale(&mut shell);
+        let changed = match command::run_with_timeout(&mut shell, timeout).ok() {
+            Some(out) => match out.lines().next().map(str::trim) {
+                Some(text) if !text.is_empty() => outputs.set(name, text.to_string()),
+                _ => outputs.remove(name),
+            },
+            None => false,
+        };
+        if changed {
+            crate::wake(&pair);
+        }
+        thread::sleep(Duration::from_secs(script.interval.max(1)));
+    }
+}
@@ -0,0 +1,66 @@
+//! Logged-in sessions, with remote (SSH) logins counted separately.
+
+use super::run_shell;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Sessions {
+    pub total: u32,
+    pub remote: u32,
+}
+
+/// Count sessions from `who`, see [`is_remote`].
+pub fn get_sessions() -> Option<Sessions> {
+    let stdout = run_shell("who")?;
+    let mut sessions = Sessions {
+        total: 0,
+        remote: 0,
+    };
+    for line in stdout.lines().filter(|l| !l.trim().is_empty()) {
+        sessions.total += 1;
+        if is_remote(line) {
+            sessions.remote += 1;
+        }
+    }
+    Some(sessions)
+}
+
+/// Whether a `who` line is a remote login: its comment column, in
+/// parentheses, is a host name rather than a local X display like `(:0)`
+/// or the pane of a terminal multiplexer like `(tmux(1234).%0)`.
+fn is_remote(line: &str) -> bool {
+    let host = line
+        .split_once(" (")
+        .and_then(|(_, rest)| rest.trim_end().strip_suffix(')'));
+    host.is_some_and(|h| {
+        !h.is_empty() && !h.starts_with(':') && !h.starts_with("tmux(") && !h.starts_with("screen")
+    })
+}
+
+pub const ICON: &str = "";
+
+#[cfg(test)]
+mod tests {
+    use super::is_remote;
+
+    #[test]
+    fn ssh_login_is_remote() {
+        assert!(is_remote(
+            "alice    pts/1        2026-10-16 09:12 (192.168.1.20)"
+        ));
+        assert!(is_remote(
+            "alice    pts/2        2026-10-16 09:12 (laptop.lan)"
+        ));
+    }
+
+    #[test]
+    fn local_sessions_are_not_remote() {
+        assert!(!is_remote("alice    tty1         2026-10-16 08:00"));
+        assert!(!is_remote("alice    seat0        2026-10-16 08:00 (:0)"));
+        assert!(!is_remote(
+            "alice    pts/3        2026-10-16 09:30 (tmux(1234).%0)"
+        ));
+        assert!(!is_remote(
+            "alice    pts/4        2026-10-16 09:31 (screen)"
+        ));
+    }
+}
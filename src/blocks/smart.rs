@@ -0,0 +1,80 @@
+//! Drive health and temperature from `smartctl`, which is slow and usually
+//! needs root, so it is only run from a poller on a long interval.
+
+use crate::command;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, PartialEq)]
+pub struct Drive {
+    /// Device name without `/dev/`, e.g. `sda`.
+    pub name: String,
+    /// Overall health assessment; `None` when smartctl could not read the
+    /// drive, most often for lack of permission.
+    pub passed: Option<bool>,
+    /// Degrees Celsius.
+    pub temperature: Option<f32>,
+}
+
+/// Query each device. A device that cannot be read is still listed, with
+/// nothing known about it.
+pub fn get_drives(devices: &[String]) -> Vec<Drive> {
+    devices.iter().map(|device| read_drive(device)).collect()
+}
+
+fn read_drive(device: &str) -> Drive {
+    let name = Path::new(device)
+        .file_name()
+        .map_or(device.into(), |name| name.to_string_lossy().into_owned());
+    let mut drive = Drive {
+        name,
+        passed: None,
+        temperature: None,
+    };
+    let output = match command::output_with_timeout(
+        Command::new("smartctl").args(["-A", "-H", device]),
+        crate::COMMAND_TIMEOUT,
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            command::log_failure("smartctl", &e.to_string());
+            return drive;
+        }
+    };
+    // The exit status is a bit mask: the low three bits mean the device
+    // could not be queried at all, the rest report problems the output
+    // describes.
+    if output.status.code().is_none_or(|code| code & 0b111 != 0) {
+        return drive;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in stdout.lines() {
+        if let Some((_, result)) = line
+            .split_once("self-assessment test result:")
+            .or_else(|| line.split_once("SMART Health Status:"))
+        {
+            let result = result.trim();
+            drive.passed = Some(result == "PASSED" || result == "OK");
+        } else if drive.temperature.is_none() {
+            drive.temperature = parse_temperature(line);
+        }
+    }
+    drive
+}
+
+/// NVMe drives print `Temperature: 38 Celsius`; ATA drives have a
+/// `Temperature_Celsius` attribute whose raw value, the tenth column, may be
+/// followed by a min/max note.
+fn parse_temperature(line: &str) -> Option<f32> {
+    if let Some(rest) = line.strip_prefix("Temperature:") {
+        return rest.split_whitespace().next()?.parse().ok();
+    }
+    let columns: Vec<&str> = line.split_whitespace().collect();
+    let attribute = columns.get(1)?;
+    if *attribute == "Temperature_Celsius" || *attribute == "Airflow_Temperature_Cel" {
+        return columns.get(9)?.parse().ok();
+    }
+    None
+}
+
+pub const ICON: &str = "";
@@ -0,0 +1,85 @@
+//! Sunrise and sunset times computed offline with the sunrise equation.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// What the sun does on a given day at a given place.
+pub enum Daylight {
+    Normal {
+        rise: DateTime<Utc>,
+        set: DateTime<Utc>,
+    },
+    /// The sun stays up all day.
+    PolarDay,
+    /// The sun stays below the horizon all day.
+    PolarNight,
+}
+
+/// The next thing the sun does after some moment.
+pub enum Next {
+    Rise(DateTime<Utc>),
+    Set(DateTime<Utc>),
+    PolarDay,
+    PolarNight,
+}
+
+/// Julian day at the Unix epoch.
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+/// Julian day of 2000-01-01 12:00 UTC.
+const J2000: f64 = 2451545.0;
+
+/// Sunrise and sunset on `date` at `latitude`/`longitude` (degrees, north
+/// and east positive), accurate to a minute or so.
+pub fn daylight(date: NaiveDate, latitude: f64, longitude: f64) -> Daylight {
+    let epoch_days = (date - DateTime::UNIX_EPOCH.date_naive()).num_days() as f64;
+    let n = (epoch_days + UNIX_EPOCH_JD + 0.5 - J2000 + 0.0008).round();
+    let mean_solar_noon = n - longitude / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_solar_noon).rem_euclid(360.0);
+    let m = anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic = (anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_solar_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic).sin();
+    let declination = (ecliptic.sin() * 23.4397f64.to_radians().sin()).asin();
+    let phi = latitude.to_radians();
+    let cos_hour_angle = ((-0.833f64).to_radians().sin() - phi.sin() * declination.sin())
+        / (phi.cos() * declination.cos());
+    if cos_hour_angle < -1.0 {
+        return Daylight::PolarDay;
+    }
+    if cos_hour_angle > 1.0 {
+        return Daylight::PolarNight;
+    }
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+    let to_utc = |jd: f64| {
+        DateTime::from_timestamp(((jd - UNIX_EPOCH_JD) * 86400.0) as i64, 0).unwrap_or_default()
+    };
+    Daylight::Normal {
+        rise: to_utc(transit - half_day),
+        set: to_utc(transit + half_day),
+    }
+}
+
+/// The next sunrise or sunset after `now`, looking at `today` and the day
+/// after; polar days and nights are reported as such.
+pub fn next(now: DateTime<Utc>, today: NaiveDate, latitude: f64, longitude: f64) -> Next {
+    for date in today.iter_days().take(2) {
+        match daylight(date, latitude, longitude) {
+            Daylight::Normal { rise, .. } if now < rise => return Next::Rise(rise),
+            Daylight::Normal { set, .. } if now < set => return Next::Set(set),
+            Daylight::Normal { .. } => continue,
+            Daylight::PolarDay => return Next::PolarDay,
+            Daylight::PolarNight => return Next::PolarNight,
+        }
+    }
+    // Only reached past a sunset right before a polar day or night begins.
+    match daylight(today.succ_opt().unwrap_or(today), latitude, longitude) {
+        Daylight::PolarNight => Next::PolarNight,
+        _ => Next::PolarDay,
+    }
+}
+
+pub const SUNRISE_ICON: &str = "󰖜";
+pub const SUNSET_ICON: &str = "󰖛";
+pub const POLAR_DAY_ICON: &str = "󰖙";
+pub const POLAR_NIGHT_ICON: &str = "󰖔";
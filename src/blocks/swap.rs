@@ -0,0 +1,56 @@
+//! Swap activity: pages swapped in and out per second, from the counters in
+//! `/proc/vmstat`. Steady swapping is a surer sign of memory pressure than
+//! how much swap is in use.
+
+use std::fs;
+use std::time::Instant;
+
+/// Pages per second.
+#[derive(Clone, Copy, PartialEq)]
+pub struct SwapRate {
+    pub pages_in: f32,
+    pub pages_out: f32,
+}
+
+/// `pswpin` and `pswpout` from `/proc/vmstat`, turned into rates.
+#[derive(Default)]
+pub struct VmstatTracker {
+    last: Option<(u64, u64, Instant)>,
+}
+
+impl VmstatTracker {
+    /// Read the counters and return the rates since the last call; the
+    /// first call only takes the reading to measure against.
+    pub fn update(&mut self) -> Option<SwapRate> {
+        let (pages_in, pages_out) = read_counters()?;
+        let now = Instant::now();
+        let rate = self.last.and_then(|(last_in, last_out, last_time)| {
+            let elapsed = now.duration_since(last_time).as_secs_f32();
+            (elapsed > 0.0).then(|| SwapRate {
+                pages_in: pages_in.saturating_sub(last_in) as f32 / elapsed,
+                pages_out: pages_out.saturating_sub(last_out) as f32 / elapsed,
+            })
+        });
+        self.last = Some((pages_in, pages_out, now));
+        rate
+    }
+}
+
+fn read_counters() -> Option<(u64, u64)> {
+    let data = fs::read_to_string("/proc/vmstat").ok()?;
+    let counter = |name: &str| {
+        data.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))?
+            .trim()
+            .parse::<u64>()
+            .ok()
+    };
+    Some((counter("pswpin")?, counter("pswpout")?))
+}
+
+/// Whether any swap device or file is in use, per `/proc/swaps`.
+pub fn enabled() -> bool {
+    fs::read_to_string("/proc/swaps").is_ok_and(|data| data.lines().nth(1).is_some())
+}
+
+pub const ICON: &str = "󰾴";
@@ -0,0 +1,18 @@
+//! Pending task count for taskwarrior or any other todo tool.
+
+use super::run_shell;
+use crate::config::TasksConfig;
+
+/// Run the configured command and read the pending task count from it.
+///
+/// A command printing a single number (like `task +PENDING count`) is used
+/// as is; anything else is treated as one task per non-empty line.
+pub fn get_task_count(cfg: &TasksConfig) -> Option<u32> {
+    let stdout = run_shell(&cfg.command)?;
+    match stdout.trim().parse::<u32>() {
+        Ok(count) => Some(count),
+        Err(_) => Some(stdout.lines().filter(|l| !l.trim().is_empty()).count() as u32),
+    }
+}
+
+pub const ICON: &str = "";
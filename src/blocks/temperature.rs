@@ -0,0 +1,182 @@
+//! CPU temperature, read from the first configured source that has one, and
+//! the hottest of every sensor found.
+
+use super::run_shell;
+use crate::config::{Aggregate, HottestConfig, TempSource, TempUnit, TemperatureConfig};
+use std::fs;
+use std::path::PathBuf;
+use sysinfo::Components;
+
+/// A temperature in degrees Celsius and the sensor it came from.
+#[derive(Clone, PartialEq)]
+pub struct Reading {
+    pub label: String,
+    pub celsius: f32,
+}
+
+/// Try each configured source in order and return the first reading.
+pub fn get_temperature(components: &Components, cfg: &TemperatureConfig) -> Option<f32> {
+    cfg.sources.iter().find_map(|source| match source {
+        TempSource::Sysinfo => from_components(components, cfg),
+        TempSource::Thermal => read_thermal_zone(&cfg.thermal_zone),
+        TempSource::Sensors => from_sensors(cfg),
+    })
+}
+
+/// Combine the temperatures of every component whose label matches the
+/// configured pattern.
+fn from_components(components: &Components, cfg: &TemperatureConfig) -> Option<f32> {
+    let temps: Vec<f32> = components
+        .iter()
+        .filter(|c| cfg.label.0.is_match(c.label()))
+        .filter_map(|c| c.temperature())
+        .collect();
+    aggregate(&temps, cfg.mode)
+}
+
+/// Parse `sensors -u`, where each feature label line is followed by indented
+/// `tempN_input: value` lines.
+fn from_sensors(cfg: &TemperatureConfig) -> Option<f32> {
+    let stdout = run_shell("sensors -u")?;
+    let mut label = "";
+    let mut temps = Vec::new();
+    for line in stdout.lines() {
+        if !line.starts_with(' ') {
+            label = line.trim_end_matches(':');
+            continue;
+        }
+        if let Some((key, value)) = line.trim().split_once(':')
+            && key.starts_with("temp")
+            && key.ends_with("_input")
+            && cfg.label.0.is_match(label)
+            && let Ok(temp) = value.trim().parse::<f32>()
+        {
+            temps.push(temp);
+        }
+    }
+    aggregate(&temps, cfg.mode)
+}
+
+/// Every `/sys/class/thermal/thermal_zone*` directory, in order.
+fn thermal_zones() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir("/sys/class/thermal") else {
+        return Vec::new();
+    };
+    let mut zones: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("thermal_zone"))
+        })
+        .collect();
+    zones.sort();
+    zones
+}
+
+/// Degrees from a sysfs file holding millidegrees.
+fn read_millidegrees(path: PathBuf) -> Option<f32> {
+    let millidegrees = fs::read_to_string(path).ok()?;
+    Some(millidegrees.trim().parse::<f32>().ok()? / 1000.0)
+}
+
+/// Degrees from the first `/sys/class/thermal/thermal_zone*` whose `type`
+/// is `kind`, e.g. `x86_pkg_temp`.
+pub fn read_thermal_zone(kind: &str) -> Option<f32> {
+    thermal_zones().into_iter().find_map(|zone| {
+        let zone_type = fs::read_to_string(zone.join("type")).ok()?;
+        if zone_type.trim() != kind {
+            return None;
+        }
+        read_millidegrees(zone.join("temp"))
+    })
+}
+
+/// The hottest of every component, every thermal zone and the configured
+/// extra files, labeled through `cfg.labels`.
+pub fn get_hottest(components: &Components, cfg: &HottestConfig) -> Option<Reading> {
+    let from_components = components
+        .iter()
+        .filter_map(|c| Some((c.label().to_string(), c.temperature()?)));
+    let from_zones = thermal_zones().into_iter().filter_map(|zone| {
+        let zone_type = fs::read_to_string(zone.join("type")).ok()?;
+        Some((
+            zone_type.trim().to_string(),
+            read_millidegrees(zone.join("temp"))?,
+        ))
+    });
+    let from_extra = cfg
+        .extra
+        .iter()
+        .filter_map(|(label, path)| Some((label.clone(), read_millidegrees(path.into())?)));
+    let (label, celsius) = from_components
+        .chain(from_zones)
+        .chain(from_extra)
+        .filter(|(_, celsius)| celsius.is_finite())
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    Some(Reading {
+        label: short_label(&label, cfg),
+        celsius,
+    })
+}
+
+/// The name from `cfg.labels` whose key appears in `label`, ignoring case,
+/// or `label` itself.
+fn short_label(label: &str, cfg: &HottestConfig) -> String {
+    let lower = label.to_lowercase();
+    cfg.labels
+        .iter()
+        .find(|(key, _)| lower.contains(&key.to_lowercase()))
+        .map_or_else(|| label.to_string(), |(_, name)| name.clone())
+}
+
+/// Combine readings per `mode`, ignoring non-finite ones.
+fn aggregate(temps: &[f32], mode: Aggregate) -> Option<f32> {
+    let temps: Vec<f32> = temps.iter().copied().filter(|t| t.is_finite()).collect();
+    if temps.is_empty() {
+        return None;
+    }
+    Some(match mode {
+        Aggregate::First => temps[0],
+        Aggregate::Max => temps.iter().copied().fold(f32::MIN, f32::max),
+        Aggregate::Avg => temps.iter().sum::<f32>() / temps.len() as f32,
+    })
+}
+
+pub const ICON: &str = "";
+
+/// Unit suffixes for temperatures.
+pub const CELSIUS: &str = "";
+pub const FAHRENHEIT: &str = "";
+
+/// Convert a reading, always taken in degrees Celsius, to `unit`.
+pub fn convert(celsius: f32, unit: TempUnit) -> f32 {
+    match unit {
+        TempUnit::Celsius => celsius,
+        TempUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+    }
+}
+
+/// Default warning and critical thresholds, in degrees Celsius.
+pub const THRESHOLDS: (f32, f32) = (70.0, 85.0);
+
+/// The warning and critical thresholds in `unit`: those set in the config,
+/// which are in `unit` already, or else `defaults` converted from Celsius.
+pub fn thresholds(
+    warning: Option<f32>,
+    critical: Option<f32>,
+    defaults: (f32, f32),
+    unit: TempUnit,
+) -> (f32, f32) {
+    (
+        warning.unwrap_or_else(|| convert(defaults.0, unit)),
+        critical.unwrap_or_else(|| convert(defaults.1, unit)),
+    )
+}
+
+pub fn suffix(unit: TempUnit) -> &'static str {
+    match unit {
+        TempUnit::Celsius => CELSIUS,
+        TempUnit::Fahrenheit => FAHRENHEIT,
+    }
+}
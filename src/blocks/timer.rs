@@ -0,0 +1,29 @@
+//! Focus timer counting down to a deadline set over IPC.
+
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+pub const ICON: &str = "󰔛";
+
+/// Format the remaining time as `MM:SS`, or `H:MM:SS` past an hour.
+pub fn format_timer(remaining: Duration) -> String {
+    let secs = remaining.as_secs_f64().ceil() as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m:02}:{s:02}")
+    }
+}
+
+/// Run the expiry command on its own thread so it never blocks rendering.
+/// It keeps the user's locale, like click commands.
+pub fn run_on_expire(command: &str) {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    crate::command::keep_locale(&mut shell);
+    thread::spawn(move || {
+        let _ = crate::command::run_unbounded(&mut shell);
+    });
+}
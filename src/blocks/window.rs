@@ -0,0 +1,61 @@
+//! Title of the focused window under sway or i3.
+
+use crate::config::SharedConfig;
+use crate::wm;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Follow window events and keep `slot` set to the focused window's title.
+/// Does nothing without a window manager; waits for the block to be enabled before
+/// starting the subscription.
+pub fn spawn_subscriber(
+    slot: Arc<Mutex<Option<String>>>,
+    config: SharedConfig,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    if !wm::available() {
+        return;
+    }
+    thread::spawn(move || {
+        while !config.read().unwrap().window.enabled {
+            thread::sleep(Duration::from_secs(5));
+        }
+        let set = |title: Option<String>| {
+            let mut slot = slot.lock().unwrap();
+            if *slot != title {
+                *slot = title;
+                crate::wake(&pair);
+            }
+        };
+        let seed = || {
+            let focused = wm::get_tree().and_then(|tree| {
+                wm::find_focused(&tree).and_then(|n| n["name"].as_str().map(str::to_string))
+            });
+            set(focused);
+        };
+        wm::follow("window", &["window"], seed, |event| {
+            let container = &event["container"];
+            if container["focused"].as_bool() != Some(true) {
+                return;
+            }
+            match event["change"].as_str() {
+                Some("focus" | "title") => set(container["name"].as_str().map(str::to_string)),
+                Some("close") => set(None),
+                _ => {}
+            }
+        });
+    });
+}
+
+/// Cut `title` to `max` characters, marking the cut with an ellipsis.
+pub fn truncate(title: &str, max: usize) -> String {
+    if title.chars().count() <= max {
+        return title.to_string();
+    }
+    let mut cut: String = title.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+pub const ICON: &str = "";
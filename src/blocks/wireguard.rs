@@ -0,0 +1,82 @@
+//! Active WireGuard tunnels and how long ago each last completed a
+//! handshake with its peer.
+
+use crate::command;
+use std::fs;
+use std::process::Command;
+
+#[derive(Clone, PartialEq)]
+pub struct Tunnel {
+    pub iface: String,
+    /// Unix time of the most recent handshake of any peer; `None` before
+    /// the first, or when `wg` may not read it.
+    pub handshake: Option<i64>,
+}
+
+/// Interfaces the kernel reports as WireGuard devices.
+fn interfaces() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            fs::read_to_string(entry.path().join("uevent"))
+                .is_ok_and(|uevent| uevent.lines().any(|l| l == "DEVTYPE=wireguard"))
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Every WireGuard interface that is up. Returns `None` when there is none,
+/// so `wg` is only run on machines using WireGuard.
+pub fn get_tunnels() -> Option<Vec<Tunnel>> {
+    let names = interfaces();
+    if names.is_empty() {
+        return None;
+    }
+    // Reading handshakes needs CAP_NET_ADMIN; without it the tunnels are
+    // still listed.
+    let stdout = command::run_with_timeout(
+        Command::new("wg").args(["show", "all", "latest-handshakes"]),
+        crate::COMMAND_TIMEOUT,
+    )
+    .unwrap_or_default();
+    let tunnels = names
+        .into_iter()
+        .map(|iface| {
+            let handshake = latest_handshake(&stdout, &iface);
+            Tunnel { iface, handshake }
+        })
+        .collect();
+    Some(tunnels)
+}
+
+/// `wg show all latest-handshakes` prints `iface\tpeer-key\tunix-time` per
+/// peer, with 0 for peers that never completed one.
+fn latest_handshake(stdout: &str, iface: &str) -> Option<i64> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != iface {
+                return None;
+            }
+            fields.nth(1)?.parse::<i64>().ok()
+        })
+        .filter(|time| *time > 0)
+        .max()
+}
+
+/// Time since the handshake as `35s`, `4m` or `2h`.
+pub fn age(seconds: i64) -> String {
+    match seconds.max(0) {
+        s if s < 60 => format!("{s}s"),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h", s / 3600),
+    }
+}
+
+pub const ICON: &str = "󰖂";
@@ -0,0 +1,65 @@
+//! ZFS pool health and scrub progress from `zpool status`.
+
+use crate::command;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, PartialEq)]
+pub struct Pool {
+    pub name: String,
+    /// `ONLINE`, `DEGRADED`, `FAULTED` and so on.
+    pub state: String,
+    /// Percentage done of a running scrub or resilver.
+    pub scan: Option<f32>,
+}
+
+impl Pool {
+    pub fn is_online(&self) -> bool {
+        self.state == "ONLINE"
+    }
+}
+
+/// Read every imported pool. Returns `None` when the ZFS module is not
+/// loaded, so machines without ZFS never run `zpool`.
+pub fn get_pools() -> Option<Vec<Pool>> {
+    if !Path::new("/proc/spl/kstat/zfs").exists() {
+        return None;
+    }
+    let stdout =
+        command::run_with_timeout(Command::new("zpool").arg("status"), crate::COMMAND_TIMEOUT)
+            .ok()?;
+    Some(parse_status(&stdout))
+}
+
+/// Each pool starts with a `pool: tank` line followed by its `state:`; while
+/// a scrub or resilver runs, a later line reads `..., 26.67% done, ...`.
+fn parse_status(stdout: &str) -> Vec<Pool> {
+    let mut pools: Vec<Pool> = Vec::new();
+    for line in stdout.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("pool:") {
+            pools.push(Pool {
+                name: name.trim().to_string(),
+                state: String::new(),
+                scan: None,
+            });
+            continue;
+        }
+        let Some(pool) = pools.last_mut() else {
+            continue;
+        };
+        if let Some(state) = line.strip_prefix("state:") {
+            pool.state = state.trim().to_string();
+        } else if let Some(done) = line
+            .split(", ")
+            .find_map(|part| part.strip_suffix("% done"))
+            .and_then(|done| done.trim().parse().ok())
+        {
+            pool.scan = Some(done);
+        }
+    }
+    pools
+}
+
+pub const ICON: &str = "";
+pub const ONLINE_ICON: &str = "";
+pub const FAULT_ICON: &str = "";
@@ -0,0 +1,105 @@
+//! Latest values of blocks produced by background threads, with the time
+//! they were last refreshed so readers can tell when a value went stale.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct CachedValue {
+    pub value: String,
+    pub updated: Instant,
+}
+
+impl CachedValue {
+    /// Whether the value is older than `max_age`; never without a limit.
+    pub fn is_stale(&self, max_age: Option<Duration>) -> bool {
+        max_age.is_some_and(|max| self.updated.elapsed() > max)
+    }
+}
+
+/// Values keyed by block name.
+#[derive(Default)]
+pub struct BlockCache(Mutex<HashMap<String, CachedValue>>);
+
+impl BlockCache {
+    /// Store a fresh value. Returns whether it differs from the previous one.
+    pub fn set(&self, name: &str, value: String) -> bool {
+        let mut map = self.0.lock().unwrap();
+        let changed = map.get(name).is_none_or(|old| old.value != value);
+        map.insert(
+            name.to_string(),
+            CachedValue {
+                value,
+                updated: Instant::now(),
+            },
+        );
+        changed
+    }
+
+    /// Drop a value. Returns whether there was one.
+    pub fn remove(&self, name: &str) -> bool {
+        self.0.lock().unwrap().remove(name).is_some()
+    }
+
+    /// The value for `name`, or `placeholder` when it is older than `max_age`.
+    /// `None` if there is no value, or it is stale and there is no placeholder.
+    pub fn get(
+        &self,
+        name: &str,
+        max_age: Option<Duration>,
+        placeholder: Option<&str>,
+    ) -> Option<String> {
+        let map = self.0.lock().unwrap();
+        let cached = map.get(name)?;
+        if cached.is_stale(max_age) {
+            placeholder.map(str::to_string)
+        } else {
+            Some(cached.value.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockCache;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn set_reports_whether_the_value_changed() {
+        let cache = BlockCache::default();
+        assert!(cache.set("a", "1".to_string()));
+        assert!(!cache.set("a", "1".to_string()));
+        assert!(cache.set("a", "2".to_string()));
+        assert_eq!(cache.get("a", None, None).as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn remove_reports_whether_there_was_a_value() {
+        let cache = BlockCache::default();
+        assert!(!cache.remove("a"));
+        cache.set("a", "1".to_string());
+        assert!(cache.remove("a"));
+        assert_eq!(cache.get("a", None, Some("?")), None);
+    }
+
+    #[test]
+    fn stale_values_give_the_placeholder_or_nothing() {
+        let cache = BlockCache::default();
+        cache.set("a", "1".to_string());
+        let max_age = Some(Duration::from_millis(1));
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(cache.get("a", max_age, Some("?")).as_deref(), Some("?"));
+        assert_eq!(cache.get("a", max_age, None), None);
+        // Without a limit a value never goes stale.
+        assert_eq!(cache.get("a", None, Some("?")).as_deref(), Some("1"));
+        // A fresh value replaces the placeholder again.
+        cache.set("a", "1".to_string());
+        assert_eq!(
+            cache
+                .get("a", Some(Duration::from_secs(60)), Some("?"))
+                .as_deref(),
+            Some("1")
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Click events sent by i3bar/swaybar on stdin once `click_events` is
+//! enabled in the protocol header.
+
+use crate::blocks::{brightness, caffeine, gammastep, kbd_backlight, power_profile};
+use crate::command;
+use crate::config::{ClickConfig, Config, SharedConfig};
+use serde::Deserialize;
+use std::io::{self, BufRead};
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const BUTTON_LEFT: u32 = 1;
+pub const BUTTON_MIDDLE: u32 = 2;
+pub const BUTTON_RIGHT: u32 = 3;
+pub const BUTTON_SCROLL_UP: u32 = 4;
+pub const BUTTON_SCROLL_DOWN: u32 = 5;
+
+/// Runtime state changed by clicks and read when drawing the bar.
+#[derive(Default)]
+pub struct ClickState {
+    /// The running idle inhibitor, if caffeine is on.
+    pub caffeine: Mutex<Option<Child>>,
+    /// Block name, instance and time of the last left click, for spotting
+    /// double clicks.
+    pub last_left_click: Mutex<Option<(String, Option<String>, Instant)>>,
+    /// Set by a middle click on the net block; cleared once the peaks are.
+    pub reset_net_peaks: AtomicBool,
+    /// Time of the last left click on the clock.
+    pub clock_clicked: Mutex<Option<Instant>>,
+}
+
+/// The fields of a click event rocketbar uses; the rest are ignored.
+#[derive(Deserialize)]
+pub struct ClickEvent {
+    #[serde(default)]
+    pub name: String,
+    pub instance: Option<String>,
+    pub button: u32,
+}
+
+/// The bar sends an endless JSON array, one event per line: `[` first, then
+/// every event after the first prefixed with a comma.
+fn parse_event(line: &str) -> Option<ClickEvent> {
+    let line = line.trim().trim_start_matches(['[', ',']).trim_start();
+    if line.is_empty() {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+/// The click settings for the clicked block. An entry for its instance
+/// (`[clicks."name:instance"]`) comes before the one for its name.
+fn entries<'a>(event: &ClickEvent, config: &'a Config) -> impl Iterator<Item = &'a ClickConfig> {
+    let by_instance = event
+        .instance
+        .as_ref()
+        .and_then(|instance| config.clicks.get(&format!("{}:{instance}", event.name)));
+    let by_name = config.clicks.get(&event.name);
+    [by_instance, by_name].into_iter().flatten()
+}
+
+/// The command configured for the clicked button.
+fn configured_action<'a>(event: &ClickEvent, config: &'a Config) -> Option<&'a String> {
+    entries(event, config).find_map(|actions| match event.button {
+        BUTTON_LEFT => actions.on_left_click.as_ref(),
+        BUTTON_MIDDLE => actions.on_middle_click.as_ref(),
+        BUTTON_RIGHT => actions.on_right_click.as_ref(),
+        BUTTON_SCROLL_UP => actions.on_scroll_up.as_ref(),
+        BUTTON_SCROLL_DOWN => actions.on_scroll_down.as_ref(),
+        _ => None,
+    })
+}
+
+/// Whether this left click completes a double click. Clicks are only tracked
+/// for blocks with a double-click action; the first click still runs the
+/// single-click action.
+fn is_double_click(event: &ClickEvent, actions: &ClickConfig, state: &ClickState) -> bool {
+    let mut last = state.last_left_click.lock().unwrap();
+    let now = Instant::now();
+    let window = Duration::from_millis(actions.double_click_ms);
+    if let Some((name, instance, at)) = last.take()
+        && name == event.name
+        && instance == event.instance
+        && now.duration_since(at) <= window
+    {
+        return true;
+    }
+    *last = Some((event.name.clone(), event.instance.clone(), now));
+    false
+}
+
+/// Run a configured action on its own thread so a slow command does not hold
+/// up further events, then redraw in case it changed what the bar shows.
+fn run_action(action: &str, event: &ClickEvent, pair: &Arc<(Mutex<bool>, Condvar)>) {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(action)
+        .env("BLOCK_NAME", &event.name)
+        .env(
+            "BLOCK_INSTANCE",
+            event.instance.as_deref().unwrap_or_default(),
+        )
+        .env("BLOCK_BUTTON", event.button.to_string());
+    command::keep_locale(&mut cmd);
+    let pair = Arc::clone(pair);
+    thread::spawn(move || {
+        let _ = command::run_unbounded(&mut cmd);
+        crate::wake(&pair);
+    });
+}
+
+/// Act on a click. Configured commands take precedence over built-in
+/// behavior. Returns whether the bar should be redrawn right away.
+fn handle_click(
+    event: &ClickEvent,
+    config: &Config,
+    state: &ClickState,
+    pair: &Arc<(Mutex<bool>, Condvar)>,
+) -> bool {
+    if event.button == BUTTON_LEFT
+        && let Some((actions, action)) = entries(event, config)
+            .find_map(|actions| Some((actions, actions.on_double_click.as_ref()?)))
+        && is_double_click(event, actions, state)
+    {
+        run_action(action, event, pair);
+        return false;
+    }
+    if let Some(action) = configured_action(event, config) {
+        run_action(action, event, pair);
+        return false;
+    }
+    match (event.name.as_str(), event.button) {
+        ("brightness", BUTTON_SCROLL_UP | BUTTON_SCROLL_DOWN) => {
+            let steps = if event.button == BUTTON_SCROLL_UP {
+                1
+            } else {
+                -1
+            };
+            if let Err(e) = brightness::adjust_brightness(&config.brightness, steps) {
+                eprintln!("rocketbar: brightness: {e}");
+            }
+            true
+        }
+        ("kbd_backlight", BUTTON_SCROLL_UP | BUTTON_SCROLL_DOWN) => {
+            let steps = if event.button == BUTTON_SCROLL_UP {
+                1
+            } else {
+                -1
+            };
+            if let Err(e) = kbd_backlight::adjust(&config.kbd_backlight, steps) {
+                eprintln!("rocketbar: kbd_backlight: {e}");
+            }
+            true
+        }
+        ("clock", BUTTON_LEFT) => {
+            *state.clock_clicked.lock().unwrap() = Some(Instant::now());
+            true
+        }
+        ("net", BUTTON_MIDDLE) => {
+            state.reset_net_peaks.store(true, Ordering::Relaxed);
+            true
+        }
+        ("power_profile", BUTTON_LEFT) => {
+            if let Err(e) = power_profile::cycle() {
+                eprintln!("rocketbar: power_profile: {e}");
+            }
+            true
+        }
+        ("gammastep", BUTTON_LEFT) => {
+            if let Err(e) = gammastep::toggle() {
+                eprintln!("rocketbar: gammastep: {e}");
+            }
+            true
+        }
+        ("caffeine", _) => {
+            if let Err(e) = caffeine::toggle(&state.caffeine, &config.caffeine) {
+                eprintln!("rocketbar: caffeine: {e}");
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Read click events from stdin for as long as the bar keeps it open.
+pub fn spawn_reader(
+    config: SharedConfig,
+    state: Arc<ClickState>,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            let Some(event) = parse_event(&line) else {
+                continue;
+            };
+            let config = config.read().unwrap().clone();
+            if handle_click(&event, &config, &state, &pair) {
+                crate::wake(&pair);
+            }
+        }
+    });
+}
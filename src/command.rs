@@ -0,0 +1,228 @@
+//! Running external programs and reading their output.
+
+use nix::sys::signal::{Signal, killpg};
+use nix::unistd::Pid;
+use std::collections::BTreeSet;
+use std::env;
+use std::io::{self, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Command lines whose last run failed, so a command failing on every poll
+/// is only logged once until it succeeds again.
+static FAILING: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// How often a command run with a timeout is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Run `command` and return its stdout with surrounding whitespace trimmed.
+/// It runs in the C locale, see [`c_locale`], and is killed if it has not
+/// exited within [`crate::COMMAND_TIMEOUT`], see [`run_with_timeout`].
+///
+/// A nonzero exit is logged together with the first line of stderr and
+/// returned as an error, as is output that is not valid UTF-8, rather than
+/// letting callers parse whatever bytes came back.
+pub fn run(command: &mut Command) -> io::Result<String> {
+    run_with_timeout(command, crate::COMMAND_TIMEOUT)
+}
+
+/// Like [`run`], but wait for the child however long it takes. Only for
+/// programs the user asked to start, like click actions, which may open
+/// something that is meant to stay up.
+pub fn run_unbounded(command: &mut Command) -> io::Result<String> {
+    c_locale(command);
+    let (program, line) = describe(command);
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => {
+            log_failure(&line, &format!("{program}: {e}"));
+            return Err(e);
+        }
+    };
+    finish(&program, &line, output)
+}
+
+/// Like [`run`], but with a timeout of `timeout` instead of the default one.
+pub fn run_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<String> {
+    let (program, line) = describe(command);
+    match output_with_timeout(command, timeout) {
+        Ok(output) => finish(&program, &line, output),
+        Err(e) => {
+            log_failure(&line, &e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Run `command` in the C locale and return its raw output whatever its exit
+/// status, for programs whose status needs more than success or failure.
+/// The child is killed and a `TimedOut` error returned if it has not exited
+/// within `timeout`, so a hung program cannot block the caller. It runs in
+/// its own process group, so anything it started, like the programs of a
+/// shell script, is killed along with it. Something it left running that
+/// still holds its output open is killed at the same deadline, keeping what
+/// was read until then. Nothing is logged.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Output> {
+    c_locale(command);
+    let (program, _) = describe(command);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("{program}: {e}")))?;
+    // Drain both pipes while waiting, so a chatty child cannot fill one and
+    // block before it exits.
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait()? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = killpg(Pid::from_raw(child.id() as i32), Signal::SIGKILL);
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{program}: timed out after {timeout:?}"),
+                ));
+            }
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    };
+    if ![&stdout, &stderr]
+        .into_iter()
+        .flatten()
+        .all(|pipe| pipe.wait(deadline))
+    {
+        let _ = killpg(Pid::from_raw(child.id() as i32), Signal::SIGKILL);
+    }
+    let collect = |pipe: Option<Drain>| pipe.map(|pipe| pipe.take()).unwrap_or_default();
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Run `command` in the C locale, so programs print the English messages and
+/// plain number formats the parsers expect whatever the user's language.
+/// Left alone when the caller set a locale, see [`keep_locale`].
+fn c_locale(command: &mut Command) {
+    if !command.get_envs().any(|(key, _)| key == "LC_ALL") {
+        command.env("LC_ALL", "C").env("LANG", "C");
+    }
+}
+
+/// Have `command` inherit the user's locale rather than run in the C one,
+/// for commands from the config whose output is shown as is or which start
+/// programs the user sees.
+pub fn keep_locale(command: &mut Command) -> &mut Command {
+    match env::var_os("LC_ALL") {
+        Some(value) => command.env("LC_ALL", value),
+        None => command.env_remove("LC_ALL"),
+    }
+}
+
+/// Read a pipe to its end on a separate thread.
+fn drain(mut pipe: impl Read + Send + 'static) -> Drain {
+    let buf = Arc::new(Mutex::new(Vec::new()));
+    let (closed, done) = mpsc::channel();
+    let shared = Arc::clone(&buf);
+    thread::spawn(move || {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => shared.lock().unwrap().extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        let _ = closed.send(());
+    });
+    Drain { buf, done }
+}
+
+/// A pipe being read on its own thread until it is closed.
+struct Drain {
+    buf: Arc<Mutex<Vec<u8>>>,
+    done: Receiver<()>,
+}
+
+impl Drain {
+    /// Wait for the pipe to be closed, at most until `deadline`. Returns
+    /// whether it was.
+    fn wait(&self, deadline: Instant) -> bool {
+        let left = deadline.saturating_duration_since(Instant::now());
+        self.done.recv_timeout(left).is_ok()
+    }
+
+    /// What has been read so far.
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.buf.lock().unwrap())
+    }
+}
+
+/// The program name and the full command line, for messages.
+fn describe(command: &Command) -> (String, String) {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let line = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (program, line)
+}
+
+/// Check the exit status of a finished command and decode its stdout.
+fn finish(program: &str, line: &str, output: Output) -> io::Result<String> {
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = match stderr.lines().next() {
+            Some(reason) => format!("{program}: {}: {reason}", output.status),
+            None => format!("{program}: {}", output.status),
+        };
+        log_failure(line, &message);
+        return Err(io::Error::other(format!("{program}: {}", output.status)));
+    }
+    FAILING.lock().unwrap().remove(line);
+    String::from_utf8(output.stdout)
+        .map(|stdout| stdout.trim().to_string())
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{program}: output is not UTF-8"),
+            )
+        })
+}
+
+/// Log `message` unless `line` is already known to be failing.
+pub fn log_failure(line: &str, message: &str) {
+    if FAILING.lock().unwrap().insert(line.to_string()) {
+        eprintln!("rocketbar: {message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_with_timeout;
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn background_child_holding_stdout_does_not_block() {
+        let started = Instant::now();
+        let stdout = run_with_timeout(
+            Command::new("sh").arg("-c").arg("echo hi; sleep 30 &"),
+            Duration::from_millis(500),
+        );
+        assert_eq!(stdout.unwrap(), "hi");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,2103 @@
+//! User configuration, read from `$XDG_CONFIG_HOME/rocketbar/config.toml`.
+//!
+//! Every section is optional and falls back to defaults that match the
+//! built-in bar, so an empty or missing file behaves like no config at all.
+//!
+//! Settings are taken from, in increasing precedence: the defaults, the
+//! config file, `ROCKETBAR_*` environment variables (see [`apply_env`]) and
+//! finally command line arguments.
+
+use crate::output::OutputFormat;
+use chrono::format::{Item, StrftimeItems};
+use nix::errno::Errno;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread;
+
+/// Config shared with the threads that read it, swapped out on reload.
+pub type SharedConfig = Arc<RwLock<Config>>;
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub output: OutputConfig,
+    pub style: StyleConfig,
+    pub ipc: IpcConfig,
+    pub metrics_log: MetricsLogConfig,
+    pub prometheus: PrometheusConfig,
+    /// Named groups of blocks that can be shown or hidden together.
+    pub groups: BTreeMap<String, GroupConfig>,
+    /// Conditions on a block's value, such as `"< 10"`, under which it is
+    /// hidden, keyed by block name.
+    pub hide_when: BTreeMap<String, Condition>,
+    /// Blocks that show whether their value went up (▲) or down (▼) since
+    /// the last tick, keyed by block name, with the change still counted as
+    /// steady (▬). Ignored for the battery while its own `trend` is on.
+    pub trends: BTreeMap<String, f32>,
+    /// Blocks rendered together as one, keyed by the merged block's name.
+    pub merges: BTreeMap<String, MergeConfig>,
+    /// Commands run when a block is clicked, keyed by block name or by
+    /// `name:instance`.
+    pub clicks: BTreeMap<String, ClickConfig>,
+    /// Command blocks, keyed by block name.
+    pub scripts: BTreeMap<String, ScriptConfig>,
+    pub docker: DockerConfig,
+    pub tasks: TasksConfig,
+    pub mail: MailConfig,
+    pub journal: JournalConfig,
+    pub calendar: CalendarConfig,
+    pub clipboard: ClipboardConfig,
+    pub gammastep: GammastepConfig,
+    pub sun: SunConfig,
+    pub recording: RecordingConfig,
+    pub timer: TimerConfig,
+    pub temperature: TemperatureConfig,
+    pub hottest: HottestConfig,
+    pub nvme: NvmeConfig,
+    pub raid: RaidConfig,
+    pub zfs: ZfsConfig,
+    pub smart: SmartConfig,
+    pub thermal: ThermalConfig,
+    pub git: GitConfig,
+    pub clock: ClockConfig,
+    pub cpu: CpuConfig,
+    pub memory: MemoryConfig,
+    pub swap: SwapConfig,
+    pub load: LoadConfig,
+    pub storage: StorageConfig,
+    pub volume: LevelConfig,
+    pub pipewire: PipewireConfig,
+    pub brightness: BrightnessConfig,
+    /// Keyboard backlight; scrolling changes it like the screen backlight.
+    pub kbd_backlight: BrightnessConfig,
+    pub battery: BatteryConfig,
+    pub power_profile: PowerProfileConfig,
+    pub net: NetConfig,
+    pub connectivity: ConnectivityConfig,
+    pub route: RouteConfig,
+    pub latency: LatencyConfig,
+    pub sessions: SessionsConfig,
+    pub public_ip: PublicIpConfig,
+    pub vpn: VpnConfig,
+    pub wireguard: WireguardConfig,
+    pub caffeine: CaffeineConfig,
+    pub window: WindowConfig,
+    pub layout: LayoutConfig,
+    pub binding_mode: BindingModeConfig,
+}
+
+/// Whether the config turns a block on.
+pub type Enabled = fn(&Config) -> bool;
+
+/// Every built-in block, with whether the config turns it on. Groups are not
+/// taken into account.
+pub const BUILTIN_BLOCKS: &[(&str, Enabled)] = &[
+    ("battery", |c| c.battery.enabled),
+    ("binding_mode", |c| c.binding_mode.enabled),
+    ("brightness", |_| true),
+    ("caffeine", |c| c.caffeine.enabled),
+    ("calendar", |c| c.calendar.enabled),
+    ("clipboard", |c| c.clipboard.enabled),
+    ("clock", |_| true),
+    ("connectivity", |c| c.connectivity.enabled),
+    ("cpu", |c| c.cpu.enabled),
+    ("docker", |c| c.docker.enabled),
+    ("gammastep", |c| c.gammastep.enabled),
+    ("git", |c| c.git.enabled),
+    ("hottest", |c| c.hottest.enabled),
+    ("journal", |c| c.journal.enabled),
+    ("kbd_backlight", |_| true),
+    ("latency", |c| c.latency.enabled),
+    ("layout", |c| c.layout.enabled),
+    ("load", |c| c.load.enabled),
+    ("mail", |c| c.mail.enabled),
+    ("memory", |c| c.memory.enabled),
+    ("net", |c| c.net.enabled),
+    ("nvme", |c| c.nvme.enabled),
+    ("pipewire", |c| c.pipewire.enabled),
+    ("power_profile", |c| c.power_profile.enabled),
+    ("public_ip", |c| c.public_ip.enabled),
+    ("raid", |c| c.raid.enabled),
+    ("recording", |c| c.recording.enabled),
+    ("route", |c| c.route.enabled),
+    ("sessions", |c| c.sessions.enabled),
+    ("smart", |c| c.smart.enabled),
+    ("storage", |c| c.storage.enabled),
+    ("sun", |c| c.sun.enabled),
+    ("swap", |c| c.swap.enabled),
+    ("tasks", |c| c.tasks.enabled),
+    ("temperature", |c| c.temperature.enabled),
+    ("thermal", |c| c.thermal.enabled),
+    ("timer", |_| true),
+    ("volume", |_| true),
+    ("vpn", |c| c.vpn.enabled),
+    ("window", |c| c.window.enabled),
+    ("wireguard", |c| c.wireguard.enabled),
+    ("zfs", |c| c.zfs.enabled),
+];
+
+impl Config {
+    /// Names of the blocks turned on: built-in blocks, with those that are
+    /// always on, then scripts. Groups are not taken into account.
+    pub fn enabled_blocks(&self) -> Vec<&str> {
+        BUILTIN_BLOCKS
+            .iter()
+            .filter(|(_, enabled)| enabled(self))
+            .map(|(name, _)| *name)
+            .chain(self.scripts.keys().map(String::as_str))
+            .collect()
+    }
+}
+
+/// What rocketbar prints and how.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Read once at startup; `--format` takes precedence.
+    pub format: OutputFormat,
+    /// Placed between blocks in all but the JSON outputs.
+    pub separator: String,
+    /// The block printed in `waybar` output, one module per block; read
+    /// once at startup, `--block` takes precedence.
+    pub block: Option<String>,
+    /// Text with `{block}` placeholders, e.g. `"{cpu} {memory} {clock}"`,
+    /// printed instead of the joined blocks in `plain` output and added as
+    /// `text` in `ndjson` output.
+    pub template: Option<String>,
+    /// Seconds between redraws when nothing prompts one sooner.
+    pub interval: u64,
+    /// Seconds between redraws while on battery; background polls slow down
+    /// by the same factor. Unset to keep the same pace.
+    pub battery_interval: Option<u64>,
+    /// Skip printing a line identical to the previous one; turn off for
+    /// consumers that expect a line every interval.
+    pub dedup: bool,
+    /// Show a red `stalled` block when the next tick is more than an
+    /// interval overdue.
+    pub watchdog: bool,
+    /// Unit of every temperature shown, and of the thresholds set for them.
+    /// Thresholds left unset follow the unit, e.g. a 70°C default becomes
+    /// 158°F.
+    pub temperature_unit: TempUnit,
+    pub numbers: NumberStyle,
+    /// Blocks only rebuilt when a background source wakes the bar, not on
+    /// the timer tick; meant for blocks fed by window manager events.
+    pub event_only: Vec<String>,
+    /// Characters the whole line may take; blocks are shortened or dropped
+    /// by priority until it fits. Unset for no limit.
+    pub max_width: Option<usize>,
+    /// Reverse the order of the blocks after the first block of this name,
+    /// so the last of them ends up next to it and the first at the edge.
+    pub reverse_after: Option<String>,
+    /// What blocks computed from two samples, CPU usage and net rates, show
+    /// before they have a second one.
+    pub warmup: Warmup,
+    /// Redraw on the minute rather than every `interval`, for bars whose
+    /// clock shows no seconds; other blocks then only change at the minute
+    /// or when an event wakes the bar.
+    pub align_to_minute: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::I3bar,
+            separator: " ".to_string(),
+            block: None,
+            template: None,
+            interval: 1,
+            battery_interval: None,
+            dedup: true,
+            watchdog: true,
+            temperature_unit: TempUnit::Celsius,
+            numbers: NumberStyle::default(),
+            event_only: vec!["layout".to_string(), "window".to_string()],
+            max_width: None,
+            reverse_after: None,
+            warmup: Warmup::Off,
+            align_to_minute: false,
+        }
+    }
+}
+
+/// How blocks without a meaningful first sample are shown until they have
+/// one.
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Warmup {
+    /// Show whatever the first sample gives, usually 0.
+    Off,
+    /// Leave the block out.
+    Hide,
+    /// Show `--` in place of the value.
+    Placeholder,
+}
+
+/// Separators used when printing numbers, e.g. `decimal = ","` and
+/// `thousands = "."` for `1.024,5`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NumberStyle {
+    pub decimal: String,
+    /// Placed between groups of three digits; empty for no grouping.
+    pub thousands: String,
+    /// How values are cut to the digits shown.
+    pub rounding: Rounding,
+    /// Shorten counts such as unread mail to `1.2k` or `3.4M`.
+    pub compact: bool,
+}
+
+/// How a value is cut to the digits shown, e.g. 99.6% with no decimals.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// To the nearest, halves away from zero: 100%.
+    Round,
+    /// Down: 99%.
+    Floor,
+    /// Up: 100%.
+    Ceil,
+}
+
+impl Rounding {
+    /// `value` cut to `precision` digits after the decimal point.
+    pub fn apply(self, value: f32, precision: usize) -> f64 {
+        // In f64, so a value like 0.15 is not scaled to 1.4999999.
+        let scale = 10f64.powi(precision as i32);
+        let scaled = f64::from(value) * scale;
+        let scaled = match self {
+            Rounding::Round => scaled.round(),
+            Rounding::Floor => scaled.floor(),
+            Rounding::Ceil => scaled.ceil(),
+        };
+        scaled / scale
+    }
+}
+
+impl NumberStyle {
+    /// Format a count of things, shortened in steps of 1000 in compact mode
+    /// with a decimal below 100 of a unit: `999`, `1.2k`, `345k`, `3.4M`.
+    pub fn count(&self, n: u64) -> String {
+        const UNITS: [&str; 4] = ["k", "M", "G", "T"];
+        if !self.compact || n < 1000 {
+            return n.to_string();
+        }
+        // Judged after rounding, so 99.95k shows as `100k` and not `100.0k`.
+        let precision = |value: f32| {
+            if self.rounding.apply(value, 1) < 100.0 {
+                1
+            } else {
+                0
+            }
+        };
+        let mut value = n as f32 / 1000.0;
+        let mut unit = 0;
+        // Move up a unit also when rounding would show `1000k`.
+        while unit + 1 < UNITS.len() && self.rounding.apply(value, precision(value)) >= 1000.0 {
+            value /= 1000.0;
+            unit += 1;
+        }
+        format!("{}{}", self.format(value, precision(value)), UNITS[unit])
+    }
+
+    /// Format `value` with `precision` digits after the decimal point.
+    pub fn format(&self, value: f32, precision: usize) -> String {
+        let value = self.rounding.apply(value, precision);
+        let plain = format!("{value:.precision$}");
+        let (int, fraction) = match plain.split_once('.') {
+            Some((int, fraction)) => (int, Some(fraction)),
+            None => (plain.as_str(), None),
+        };
+        let (sign, digits) = match int.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", int),
+        };
+        let mut out = sign.to_string();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                out.push_str(&self.thousands);
+            }
+            out.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            out.push_str(&self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        Self {
+            decimal: ".".to_string(),
+            thousands: String::new(),
+            rounding: Rounding::Round,
+            compact: false,
+        }
+    }
+}
+
+/// Block values appended to a file every tick, for graphing later.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsLogConfig {
+    pub enabled: bool,
+    /// `~` is expanded; missing directories are created.
+    pub path: String,
+    pub format: MetricsFormat,
+    /// Blocks whose value is logged, by name or `name:instance`, e.g.
+    /// `"net:wlp2s0"`; a block not shown that tick leaves its field empty.
+    pub blocks: Vec<String>,
+}
+
+impl Default for MetricsLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "~/.local/share/rocketbar/metrics.csv".to_string(),
+            format: MetricsFormat::Csv,
+            blocks: ["cpu", "memory", "temperature", "net"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+/// Every block value written as a Prometheus textfile each tick, for
+/// node_exporter's textfile collector.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrometheusConfig {
+    pub enabled: bool,
+    /// Should end in `.prom` and sit in the collector's directory.
+    pub path: String,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "/var/lib/node_exporter/textfile_collector/rocketbar.prom".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsFormat {
+    /// A `time` column and one per block, under a header line written when
+    /// the file is new.
+    Csv,
+    /// One JSON object per line, with `time` and a key per block.
+    Ndjson,
+}
+
+/// Unit temperatures are shown in.
+#[derive(Clone, Copy, Deserialize)]
+pub enum TempUnit {
+    #[serde(rename = "C")]
+    Celsius,
+    #[serde(rename = "F")]
+    Fahrenheit,
+}
+
+/// Spacing applied when assembling the text of every block.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    /// Placed between a block's icon and its text.
+    pub icon_spacing: String,
+    /// Spaces added before every block.
+    pub padding_left: usize,
+    /// Spaces added after every block.
+    pub padding_right: usize,
+    /// Per-block overrides keyed by block name. By default the clock gets one
+    /// space of right padding.
+    pub blocks: BTreeMap<String, BlockStyle>,
+    pub powerline: PowerlineConfig,
+    /// `ascii` shows short labels like `vol` and `bat` in place of the Nerd
+    /// Font icons, for bars without such a font.
+    pub icon_set: IconSet,
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IconSet {
+    NerdFont,
+    Ascii,
+}
+
+impl Default for StyleConfig {
+    fn default() -> Self {
+        let clock = BlockStyle {
+            padding_right: Some(1),
+            ..BlockStyle::default()
+        };
+        Self {
+            icon_spacing: "  ".to_string(),
+            padding_left: 0,
+            padding_right: 0,
+            blocks: BTreeMap::from([("clock".to_string(), clock)]),
+            powerline: PowerlineConfig::default(),
+            icon_set: IconSet::NerdFont,
+        }
+    }
+}
+
+/// Powerline-style separators between blocks, in i3bar output only.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerlineConfig {
+    pub enabled: bool,
+    /// Drawn between blocks, in the background of the block after it over
+    /// that of the block before it. Blocks of the same group share one
+    /// background and have none between them.
+    pub glyph: String,
+    /// Block backgrounds, used in turn from the left for blocks without a
+    /// `background` of their own.
+    pub backgrounds: Vec<String>,
+}
+
+impl Default for PowerlineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            glyph: "".to_string(),
+            backgrounds: vec![crate::BLACK.to_string(), crate::SURFACE.to_string()],
+        }
+    }
+}
+
+/// How a numeric block value is printed.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Format {
+    /// Digits after the decimal point.
+    pub precision: usize,
+    /// Minimum width, padded on the left.
+    pub width: usize,
+    /// Append the block's unit, such as `%`.
+    pub unit: bool,
+}
+
+impl Format {
+    pub const fn new(precision: usize, width: usize, unit: bool) -> Self {
+        Self {
+            precision,
+            width,
+            unit,
+        }
+    }
+
+    /// Format `value`, followed by `unit` when enabled.
+    pub fn apply(&self, value: f32, unit: &str, numbers: &NumberStyle) -> String {
+        let unit = if self.unit { unit } else { "" };
+        let value = numbers.format(value, self.precision);
+        format!("{value:>w$}{unit}", w = self.width)
+    }
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::new(0, 0, false)
+    }
+}
+
+/// Style overrides for a single block; unset fields use the global style.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockStyle {
+    pub icon_spacing: Option<String>,
+    pub padding_left: Option<usize>,
+    pub padding_right: Option<usize>,
+    /// Text whose width i3bar reserves for the block, so a block that comes
+    /// and goes or changes length does not shift its neighbours.
+    pub min_width: Option<String>,
+    /// Blocks with a lower priority are shortened to their icon, then
+    /// dropped first when the line exceeds `output.max_width`; 0 if unset.
+    pub priority: Option<i32>,
+    /// Which of the icon and text are shown; both if unset.
+    pub display: Option<Display>,
+    /// Background in powerline mode; the next of `powerline.backgrounds` if
+    /// unset.
+    pub background: Option<String>,
+}
+
+/// The parts of a block that are shown.
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Display {
+    IconAndText,
+    /// Just the icon, for blocks without one the text.
+    IconOnly,
+    TextOnly,
+}
+
+/// Control socket used to push state into the bar.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IpcConfig {
+    pub enabled: bool,
+    /// Socket path, defaulting to `$XDG_RUNTIME_DIR/rocketbar.sock`.
+    pub socket: Option<String>,
+}
+
+impl Default for IpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            socket: None,
+        }
+    }
+}
+
+/// A named set of blocks, toggled at runtime with `group show|hide|toggle`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroupConfig {
+    /// Block names, as they appear in the bar's `name` field.
+    pub blocks: Vec<String>,
+    pub enabled: bool,
+}
+
+impl Default for GroupConfig {
+    fn default() -> Self {
+        Self {
+            blocks: Vec::new(),
+            enabled: true,
+        }
+    }
+}
+
+/// Several blocks shown as one, e.g. cpu, memory and temperature in a
+/// single compact block.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MergeConfig {
+    /// Member block names, merged in bar order.
+    pub blocks: Vec<String>,
+    /// Placed between the members' parts.
+    pub joiner: String,
+    /// Icon of the merged block.
+    pub icon: String,
+    /// Keep each member's own icon next to its text.
+    pub icons: bool,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            blocks: Vec::new(),
+            joiner: " | ".to_string(),
+            icon: String::new(),
+            icons: false,
+        }
+    }
+}
+
+/// Shell commands run on clicks, with `BLOCK_NAME`, `BLOCK_INSTANCE` and
+/// `BLOCK_BUTTON` set in their environment.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClickConfig {
+    pub on_left_click: Option<String>,
+    pub on_middle_click: Option<String>,
+    pub on_right_click: Option<String>,
+    pub on_scroll_up: Option<String>,
+    pub on_scroll_down: Option<String>,
+    /// Run on a second left click within `double_click_ms` of the first.
+    pub on_double_click: Option<String>,
+    pub double_click_ms: u64,
+}
+
+impl Default for ClickConfig {
+    fn default() -> Self {
+        Self {
+            on_left_click: None,
+            on_middle_click: None,
+            on_right_click: None,
+            on_scroll_up: None,
+            on_scroll_down: None,
+            on_double_click: None,
+            double_click_ms: 300,
+        }
+    }
+}
+
+/// A block showing the first line printed by a shell command.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScriptConfig {
+    pub command: String,
+    pub icon: String,
+    /// Seconds between runs.
+    pub interval: u64,
+    /// Seconds a run may take before the command is killed.
+    pub timeout: u64,
+    /// Seconds after the last successful run before the output counts as
+    /// stale; unset to show it indefinitely.
+    pub max_age: Option<u64>,
+    /// Shown instead of stale output; unset to hide the block.
+    pub placeholder: Option<String>,
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            icon: String::new(),
+            interval: 10,
+            timeout: 10,
+            max_age: None,
+            placeholder: None,
+        }
+    }
+}
+
+/// Running container count, e.g. from `docker ps -q`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DockerConfig {
+    pub enabled: bool,
+    /// Shell command whose output lines are counted as containers.
+    pub command: String,
+    /// Skip the block when this socket does not exist.
+    pub socket: Option<String>,
+    /// Seconds between polls.
+    pub interval: u64,
+    pub color_zero: Option<String>,
+    pub color_nonzero: Option<String>,
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: "docker ps -q".to_string(),
+            socket: Some("/var/run/docker.sock".to_string()),
+            interval: 10,
+            color_zero: None,
+            color_nonzero: Some(crate::BLUE.to_string()),
+        }
+    }
+}
+
+/// Pending task count, e.g. from taskwarrior.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TasksConfig {
+    pub enabled: bool,
+    /// Shell command printing either a count or one task per line.
+    pub command: String,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for TasksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: "task +PENDING count".to_string(),
+            interval: 60,
+        }
+    }
+}
+
+/// Unread mail count from local maildirs.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    pub enabled: bool,
+    /// Maildirs whose `new/` messages are counted; `~` is expanded.
+    pub maildirs: Vec<String>,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            maildirs: vec!["~/Mail/INBOX".to_string()],
+            interval: 5,
+        }
+    }
+}
+
+/// Count of recent journal entries, e.g. errors; hidden when there are none.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JournalConfig {
+    pub enabled: bool,
+    /// Lowest priority counted, by name (`err`, `warning`) or number.
+    pub priority: String,
+    /// Seconds back to count entries from.
+    pub window: u64,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            priority: "err".to_string(),
+            window: 300,
+            interval: 30,
+        }
+    }
+}
+
+/// Time until the next calendar event, e.g. `in 25m: Standup`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CalendarConfig {
+    pub enabled: bool,
+    /// Shell command listing the coming events like `khal list` does: day
+    /// headings followed by `HH:MM[-HH:MM] Title` lines.
+    pub command: String,
+    /// Seconds between runs of the command; the countdown itself is
+    /// updated every tick.
+    pub interval: u64,
+    /// Minutes before an event from which the block turns yellow.
+    pub warning: u64,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: "khal list now 12h".to_string(),
+            interval: 60,
+            warning: 5,
+        }
+    }
+}
+
+/// Whether the clipboard holds anything.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+    pub enabled: bool,
+    /// Show how many bytes it holds.
+    pub show_size: bool,
+    /// Leave the block out while the clipboard is empty, rather than show
+    /// `empty`.
+    pub hide_empty: bool,
+    /// Seconds between polls where changes cannot be watched for (X11).
+    pub interval: u64,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            show_size: false,
+            hide_empty: true,
+            interval: 5,
+        }
+    }
+}
+
+/// Color temperature set by a running gammastep; a click toggles it.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GammastepConfig {
+    pub enabled: bool,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for GammastepConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 60,
+        }
+    }
+}
+
+/// The next sunrise or sunset, computed offline for a fixed place.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SunConfig {
+    pub enabled: bool,
+    /// Degrees, north positive.
+    pub latitude: f64,
+    /// Degrees, east positive.
+    pub longitude: f64,
+    /// Show the time left, e.g. `in 2h05m`, instead of the time of day.
+    pub countdown: bool,
+}
+
+/// Alternate a block between two colors every tick while it is alerting.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlinkConfig {
+    pub enabled: bool,
+    pub colors: [String; 2],
+}
+
+impl Default for BlinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colors: [crate::RED.to_string(), crate::WHITE.to_string()],
+        }
+    }
+}
+
+/// Screen recording indicator.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    /// Process names that count as an active recording.
+    pub processes: Vec<String>,
+    /// Seconds between polls.
+    pub interval: u64,
+    pub blink: BlinkConfig,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            processes: ["obs", "wf-recorder", "wl-screenrec"]
+                .map(String::from)
+                .to_vec(),
+            interval: 2,
+            blink: BlinkConfig {
+                enabled: true,
+                colors: [crate::RED.to_string(), crate::WHITE.to_string()],
+            },
+        }
+    }
+}
+
+/// Focus timer driven by `rocketbar-ctl timer start <duration>`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimerConfig {
+    /// Shell command run once when the timer reaches zero.
+    pub on_expire: Option<String>,
+    pub blink: BlinkConfig,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self {
+            on_expire: Some("notify-send rocketbar 'Timer finished'".to_string()),
+            blink: BlinkConfig {
+                enabled: true,
+                ..BlinkConfig::default()
+            },
+        }
+    }
+}
+
+/// A regular expression, validated when the config is parsed.
+#[derive(Clone)]
+pub struct Pattern(pub Regex);
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(Pattern)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A strftime format string, validated when the config is parsed.
+#[derive(Clone)]
+pub struct TimeFormat(pub String);
+
+impl<'de> Deserialize<'de> for TimeFormat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let format = String::deserialize(deserializer)?;
+        if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+            return Err(serde::de::Error::custom(format!(
+                "invalid strftime format: {format}"
+            )));
+        }
+        Ok(TimeFormat(format))
+    }
+}
+
+/// An address range in CIDR notation, validated when the config is parsed.
+#[derive(Clone)]
+pub struct Cidr {
+    pub addr: IpAddr,
+    pub prefix: u32,
+}
+
+impl Cidr {
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix).unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix).unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Cidr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let range = String::deserialize(deserializer)?;
+        let invalid = || serde::de::Error::custom(format!("invalid address range: {range}"));
+        let (addr, prefix) = match range.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (range.as_str(), None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse().map_err(|_| invalid())?,
+            None => max,
+        };
+        if prefix > max {
+            return Err(invalid());
+        }
+        Ok(Cidr { addr, prefix })
+    }
+}
+
+/// A comparison against a number, written like `"< 10"` or `"== 0"`.
+#[derive(Clone)]
+pub struct Condition {
+    pub op: CmpOp,
+    pub value: f32,
+}
+
+#[derive(Clone, Copy)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Condition {
+    pub fn matches(&self, value: f32) -> bool {
+        match self.op {
+            CmpOp::Lt => value < self.value,
+            CmpOp::Le => value <= self.value,
+            CmpOp::Gt => value > self.value,
+            CmpOp::Ge => value >= self.value,
+            CmpOp::Eq => value == self.value,
+            CmpOp::Ne => value != self.value,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let invalid = || serde::de::Error::custom(format!("invalid condition: {text}"));
+        let trimmed = text.trim();
+        // Two-character operators first, so `<=` is not read as `<`.
+        let (symbol, op) = [
+            ("<=", CmpOp::Le),
+            (">=", CmpOp::Ge),
+            ("==", CmpOp::Eq),
+            ("!=", CmpOp::Ne),
+            ("<", CmpOp::Lt),
+            (">", CmpOp::Gt),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| trimmed.starts_with(symbol))
+        .ok_or_else(invalid)?;
+        let value = trimmed[symbol.len()..]
+            .trim()
+            .parse()
+            .map_err(|_| invalid())?;
+        Ok(Condition { op, value })
+    }
+}
+
+/// How several readings are combined into one value.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregate {
+    First,
+    Max,
+    Avg,
+}
+
+/// Where a temperature reading can come from.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempSource {
+    /// Sensor components as reported by sysinfo.
+    Sysinfo,
+    /// The `/sys/class/thermal` zone named by `thermal_zone`.
+    Thermal,
+    /// The output of `sensors -u` from lm-sensors.
+    Sensors,
+}
+
+/// CPU temperature, from the first source in `sources` that has a reading.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemperatureConfig {
+    pub enabled: bool,
+    pub sources: Vec<TempSource>,
+    /// Only sensors whose label matches are considered.
+    pub label: Pattern,
+    pub mode: Aggregate,
+    /// `type` of the thermal zone used by the `thermal` source.
+    pub thermal_zone: String,
+    pub format: Format,
+    /// Degrees at which the block turns yellow, in `output.temperature_unit`;
+    /// 70°C when unset.
+    pub warning: Option<f32>,
+    /// Degrees at which the block turns red; 85°C when unset.
+    pub critical: Option<f32>,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+    /// Seconds between readings.
+    pub interval: u64,
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sources: vec![
+                TempSource::Sysinfo,
+                TempSource::Thermal,
+                TempSource::Sensors,
+            ],
+            label: Pattern(Regex::new("").unwrap()),
+            mode: Aggregate::Max,
+            thermal_zone: "x86_pkg_temp".to_string(),
+            format: Format::new(0, 0, true),
+            warning: None,
+            critical: None,
+            urgent: None,
+            interval: 2,
+        }
+    }
+}
+
+/// The hottest of all temperature sensors, labeled with where it is, e.g.
+/// `GPU 72°`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HottestConfig {
+    pub enabled: bool,
+    /// Names shown for sensors, keyed by part of their label, e.g.
+    /// `amdgpu = "GPU"`; sensors without a match show their full label.
+    pub labels: BTreeMap<String, String>,
+    /// More sensors, as files holding millidegrees keyed by their label.
+    pub extra: BTreeMap<String, String>,
+    pub format: Format,
+    /// Degrees at which the block turns yellow, in `output.temperature_unit`;
+    /// 70°C when unset.
+    pub warning: Option<f32>,
+    /// Degrees at which the block turns red; 85°C when unset.
+    pub critical: Option<f32>,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+    /// Seconds between readings.
+    pub interval: u64,
+}
+
+impl Default for HottestConfig {
+    fn default() -> Self {
+        let labels = [
+            ("acpitz", "ACPI"),
+            ("amdgpu", "GPU"),
+            ("coretemp", "CPU"),
+            ("k10temp", "CPU"),
+            ("nouveau", "GPU"),
+            ("nvme", "NVMe"),
+            ("x86_pkg_temp", "CPU"),
+        ];
+        Self {
+            enabled: false,
+            labels: labels
+                .into_iter()
+                .map(|(key, name)| (key.to_string(), name.to_string()))
+                .collect(),
+            extra: BTreeMap::new(),
+            format: Format::new(0, 0, true),
+            warning: None,
+            critical: None,
+            urgent: None,
+            interval: 5,
+        }
+    }
+}
+
+/// A single `/sys/class/thermal` zone, for boards exposing nothing else.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThermalConfig {
+    pub enabled: bool,
+    /// Contents of the zone's `type` file, e.g. `cpu-thermal`.
+    pub zone: String,
+    pub format: Format,
+    /// Degrees at which the block turns yellow, in `output.temperature_unit`;
+    /// 70°C when unset.
+    pub warning: Option<f32>,
+    /// Degrees at which the block turns red; 85°C when unset.
+    pub critical: Option<f32>,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+}
+
+impl Default for ThermalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            zone: "cpu-thermal".to_string(),
+            format: Format::new(0, 0, true),
+            warning: None,
+            critical: None,
+            urgent: None,
+        }
+    }
+}
+
+/// NVMe drive temperature.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NvmeConfig {
+    pub enabled: bool,
+    pub format: Format,
+    /// Degrees at which the block turns yellow, in `output.temperature_unit`;
+    /// 60°C when unset.
+    pub warning: Option<f32>,
+    /// Degrees at which the block turns red; 70°C when unset, around where
+    /// most drives throttle.
+    pub critical: Option<f32>,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+}
+
+impl Default for NvmeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: Format::new(0, 0, true),
+            warning: None,
+            critical: None,
+            urgent: None,
+        }
+    }
+}
+
+/// Software RAID health, hidden when there are no md arrays.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RaidConfig {
+    pub enabled: bool,
+}
+
+/// ZFS pool health, hidden when ZFS is not in use.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZfsConfig {
+    pub enabled: bool,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for ZfsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 60,
+        }
+    }
+}
+
+/// PipeWire graph sample rate and quantum, e.g. `48kHz/256`; hidden when
+/// PipeWire is not running.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PipewireConfig {
+    pub enabled: bool,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for PipewireConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 5,
+        }
+    }
+}
+
+/// Drive health and temperature from SMART data.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmartConfig {
+    pub enabled: bool,
+    /// Devices passed to `smartctl`, e.g. `/dev/sda` or `/dev/nvme0`.
+    pub devices: Vec<String>,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for SmartConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            devices: Vec::new(),
+            interval: 600,
+        }
+    }
+}
+
+/// Branch and dirty state of watched repositories.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    pub enabled: bool,
+    /// Repository paths, one block each; `~` is expanded.
+    pub repos: Vec<String>,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            repos: Vec::new(),
+            interval: 5,
+        }
+    }
+}
+
+/// Clock block.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClockConfig {
+    /// strftime formats; with more than one the clock rotates through them.
+    pub formats: Vec<TimeFormat>,
+    /// Ticks to show each format before moving to the next.
+    pub rotate_every: u64,
+    /// Format shown for `click_seconds` after a left click on the clock,
+    /// e.g. to see the seconds on demand.
+    pub click_format: Option<TimeFormat>,
+    pub click_seconds: u64,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            formats: vec![TimeFormat("%H:%M".to_string())],
+            rotate_every: 5,
+            click_format: Some(TimeFormat("%H:%M:%S".to_string())),
+            click_seconds: 5,
+        }
+    }
+}
+
+/// Memory usage as a percentage.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemoryConfig {
+    pub enabled: bool,
+    pub format: Format,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+    /// Add a bar split into used (`█`), buffers/cache (`▒`) and free (`░`)
+    /// memory, each in its color where the bar supports pango markup.
+    pub bar: bool,
+    /// Cells in the bar.
+    pub bar_width: usize,
+    pub used_color: String,
+    pub cache_color: String,
+    pub free_color: String,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: Format::new(1, 4, false),
+            urgent: None,
+            bar: false,
+            bar_width: 10,
+            used_color: crate::YELLOW.to_string(),
+            cache_color: crate::BLUE.to_string(),
+            free_color: crate::DIM.to_string(),
+        }
+    }
+}
+
+/// CPU usage, sampled on its own thread.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CpuConfig {
+    pub enabled: bool,
+    pub format: Format,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+    /// Seconds between samples.
+    pub interval: u64,
+    /// How many of the latest samples are averaged.
+    pub samples: usize,
+}
+
+impl Default for CpuConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: Format::new(1, 4, false),
+            urgent: None,
+            interval: 1,
+            samples: 1,
+        }
+    }
+}
+
+/// Pages swapped in and out per second; hidden without swap.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SwapConfig {
+    pub enabled: bool,
+    /// Pages per second, in and out together, above which the block turns
+    /// red.
+    pub active_rate: f32,
+    /// Seconds between samples.
+    pub interval: u64,
+}
+
+impl Default for SwapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            active_rate: 1.0,
+            interval: 2,
+        }
+    }
+}
+
+/// Battery charge.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatteryConfig {
+    pub enabled: bool,
+    /// Name under `/sys/class/power_supply`.
+    pub device: String,
+    pub format: Format,
+    /// Percentage at or below which the block turns yellow.
+    pub warning: f32,
+    /// Percentage at or below which the block turns red.
+    pub critical: f32,
+    /// Show whether the charge went up or down last.
+    pub trend: bool,
+    /// Icons by level while discharging; empty for the built-in ones.
+    pub icons: IconRamp,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            device: "BAT0".to_string(),
+            format: Format::new(0, 0, true),
+            warning: 20.0,
+            critical: 10.0,
+            trend: true,
+            icons: IconRamp::default(),
+        }
+    }
+}
+
+/// The power-profiles-daemon profile, or the CPU governor without it; a
+/// click switches to the next profile.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerProfileConfig {
+    pub enabled: bool,
+    /// Seconds between polls, which is also how long a switch by clicking
+    /// may take to show.
+    pub interval: u64,
+}
+
+impl Default for PowerProfileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 2,
+        }
+    }
+}
+
+/// One-minute load average.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoadConfig {
+    pub enabled: bool,
+    pub format: Format,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: Format::new(1, 0, false),
+            urgent: None,
+        }
+    }
+}
+
+/// What the storage block shows.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskDisplay {
+    /// Used space as a percentage, through `format`.
+    PercentUsed,
+    /// Space left, e.g. `41.20GB`.
+    Free,
+    /// Used and total space, e.g. `190.31GB/231.51GB`.
+    UsedOfTotal,
+}
+
+/// Used space of one mounted filesystem.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub enabled: bool,
+    /// Mount point to report on.
+    pub mount: String,
+    pub display: DiskDisplay,
+    pub format: Format,
+    /// Flag the block urgent at or above this value.
+    pub urgent: Option<f32>,
+    /// Always show the percentage of inodes in use.
+    pub inodes: bool,
+    /// Show the inode percentage in red once it reaches this value, however
+    /// much space is left.
+    pub inode_alert: Option<f32>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mount: "/".to_string(),
+            display: DiskDisplay::PercentUsed,
+            format: Format::new(1, 4, false),
+            urgent: None,
+            inodes: false,
+            inode_alert: Some(90.0),
+        }
+    }
+}
+
+/// Always-on percentage blocks, currently only volume.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LevelConfig {
+    pub format: Format,
+    /// Icons by level; empty for the built-in ones.
+    pub icons: IconRamp,
+}
+
+/// Icons chosen by a block's level, e.g.
+/// `icons = [{ from = 0, icon = "a" }, { from = 50, icon = "b" }]`; each is
+/// used from its level up to the next one's. Levels must be ascending.
+#[derive(Clone, Default)]
+pub struct IconRamp(Vec<RampStep>);
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RampStep {
+    from: f32,
+    icon: String,
+}
+
+impl IconRamp {
+    /// The configured steps for `render_level`, or `builtin` if there are
+    /// none.
+    pub fn steps_or<'a>(&'a self, builtin: &[(f32, &'a str)]) -> Vec<(f32, &'a str)> {
+        if self.0.is_empty() {
+            return builtin.to_vec();
+        }
+        self.0
+            .iter()
+            .map(|step| (step.from, step.icon.as_str()))
+            .collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for IconRamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let steps = Vec::<RampStep>::deserialize(deserializer)?;
+        if steps.windows(2).any(|pair| pair[0].from >= pair[1].from) {
+            return Err(serde::de::Error::custom(
+                "icon levels must be in ascending order",
+            ));
+        }
+        Ok(IconRamp(steps))
+    }
+}
+
+/// Backlight level; scrolling on the block changes it.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrightnessConfig {
+    pub format: Format,
+    /// Icons by level; empty for the built-in ones.
+    pub icons: IconRamp,
+    /// Percentage of the full range changed per scroll step.
+    pub step: u32,
+    pub backend: BrightnessBackend,
+}
+
+impl Default for BrightnessConfig {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            icons: IconRamp::default(),
+            step: 5,
+            backend: BrightnessBackend::Brightnessctl,
+        }
+    }
+}
+
+/// How brightness changes are applied.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrightnessBackend {
+    /// `brightnessctl set`, which works without write access to sysfs.
+    Brightnessctl,
+    /// Write the `brightness` file directly; needs a udev rule or group.
+    Sysfs,
+}
+
+/// The step between unit prefixes, validated when the config is parsed.
+#[derive(Clone, Copy)]
+pub struct UnitBase(pub u32);
+
+impl<'de> Deserialize<'de> for UnitBase {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u32::deserialize(deserializer)? {
+            base @ (1000 | 1024) => Ok(UnitBase(base)),
+            base => Err(serde::de::Error::custom(format!(
+                "unit base must be 1000 or 1024, not {base}"
+            ))),
+        }
+    }
+}
+
+/// What network rates are counted in.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateUnit {
+    Bytes,
+    Bits,
+}
+
+/// Throughput of the active network interface.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetConfig {
+    pub enabled: bool,
+    /// Show every interface that is up or moving data, found by scanning,
+    /// instead of the three configured ones.
+    pub auto: bool,
+    pub vpn_interface: String,
+    pub eth_interface: String,
+    pub wifi_interface: String,
+    /// Show the NordVPN server country while the VPN is up.
+    pub vpn_country: bool,
+    /// Seconds between country lookups while the VPN is up.
+    pub country_interval: u64,
+    /// Weight of the newest sample in the smoothed rate; 1 disables smoothing.
+    pub alpha: f32,
+    /// Append the highest rates seen since startup, the last interface
+    /// change or the last reset (`net reset-peaks`, or middle click).
+    pub show_peaks: bool,
+    pub unit: RateUnit,
+    /// Step between unit prefixes, 1000 or 1024.
+    pub base: UnitBase,
+    /// Bytes per second above which the block counts as active; unset to
+    /// keep the per-interface colors.
+    pub active_rate: Option<f32>,
+    pub active_color: String,
+    pub idle_color: String,
+    /// Shown instead of the rates while nothing is being transferred.
+    pub idle_icon: Option<String>,
+    /// Short rates behind colored arrows, e.g. `↓1.2M ↑340K`.
+    pub compact: bool,
+    pub down_color: String,
+    pub up_color: String,
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto: false,
+            vpn_interface: crate::VPN_INTERFACE.to_string(),
+            eth_interface: crate::ETH_INTERFACE.to_string(),
+            wifi_interface: crate::WIFI_INTERFACE.to_string(),
+            vpn_country: true,
+            country_interval: 60,
+            alpha: 1.0,
+            show_peaks: false,
+            unit: RateUnit::Bytes,
+            base: UnitBase(1024),
+            active_rate: None,
+            active_color: crate::CYAN.to_string(),
+            idle_color: crate::DIM.to_string(),
+            idle_icon: None,
+            compact: false,
+            down_color: crate::GREEN.to_string(),
+            up_color: crate::YELLOW.to_string(),
+        }
+    }
+}
+
+/// Online indicator.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectivityConfig {
+    pub enabled: bool,
+    /// `host:port` that must accept a TCP connection; unset to only require
+    /// a default route.
+    pub target: Option<String>,
+    pub timeout_ms: u64,
+    /// Seconds between checks.
+    pub interval: u64,
+}
+
+impl Default for ConnectivityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: Some("1.1.1.1:53".to_string()),
+            timeout_ms: 1000,
+            interval: 10,
+        }
+    }
+}
+
+/// The interface carrying the default route, e.g. `via wlp2s0`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RouteConfig {
+    pub enabled: bool,
+    /// Add the route's metric, e.g. `via wlp2s0 (600)`.
+    pub show_metric: bool,
+}
+
+impl Default for RouteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            show_metric: true,
+        }
+    }
+}
+
+/// Round-trip time of a TCP connect to a known host.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LatencyConfig {
+    pub enabled: bool,
+    /// `host:port` to connect to.
+    pub target: String,
+    pub timeout_ms: u64,
+    /// Seconds between measurements.
+    pub interval: u64,
+    pub format: Format,
+    /// Milliseconds at which the block turns yellow.
+    pub warning: f32,
+    /// Milliseconds at which the block turns red.
+    pub critical: f32,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: "1.1.1.1:53".to_string(),
+            timeout_ms: 1000,
+            interval: 10,
+            format: Format::new(0, 0, true),
+            warning: 100.0,
+            critical: 300.0,
+        }
+    }
+}
+
+/// Logged-in users and SSH sessions.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionsConfig {
+    pub enabled: bool,
+    /// Hide the block while only a single local session exists.
+    pub hide_single: bool,
+    /// Seconds between polls.
+    pub interval: u64,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hide_single: true,
+            interval: 30,
+        }
+    }
+}
+
+/// Public address lookup.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PublicIpConfig {
+    pub enabled: bool,
+    /// Lookup services returning a bare address; add an IPv6-only one such
+    /// as `https://api6.ipify.org` to show both families.
+    pub urls: Vec<String>,
+    /// Request timeout in seconds.
+    pub timeout: u64,
+    /// Seconds between lookups.
+    pub interval: u64,
+    /// Expected egress ranges, e.g. `["185.65.134.0/24"]`. An address outside
+    /// all of them turns the block red.
+    pub vpn_ranges: Vec<Cidr>,
+}
+
+impl Default for PublicIpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            urls: vec!["https://api.ipify.org".to_string()],
+            timeout: 5,
+            interval: 600,
+            vpn_ranges: Vec::new(),
+        }
+    }
+}
+
+/// Alert shown while the VPN is expected but not carrying traffic.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VpnConfig {
+    pub enabled: bool,
+    pub interface: String,
+    /// Also require the default route to go through the interface. Leave off
+    /// for VPNs that route through policy rules instead of the main table.
+    pub check_route: bool,
+}
+
+impl Default for VpnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interface: crate::VPN_INTERFACE.to_string(),
+            check_route: false,
+        }
+    }
+}
+
+/// WireGuard tunnels that are up, each with the age of its last handshake.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WireguardConfig {
+    pub enabled: bool,
+    /// Seconds between polls.
+    pub interval: u64,
+    /// Seconds after which a handshake counts as stale and the tunnel turns
+    /// red. WireGuard renews them every two minutes while traffic flows.
+    pub stale: u64,
+}
+
+impl Default for WireguardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 10,
+            stale: 180,
+        }
+    }
+}
+
+/// Click-to-toggle idle inhibitor.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CaffeineConfig {
+    pub enabled: bool,
+    /// Shell command that inhibits idle for as long as it runs, e.g.
+    /// `swayidle` replaced by a no-op or `systemd-inhibit ... sleep infinity`.
+    pub command: String,
+    /// Color of the block while the inhibitor runs.
+    pub color: String,
+}
+
+impl Default for CaffeineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: "systemd-inhibit --what=idle --who=rocketbar --why=Caffeine sleep infinity"
+                .to_string(),
+            color: crate::YELLOW.to_string(),
+        }
+    }
+}
+
+/// Focused window title under sway or i3.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub enabled: bool,
+    /// Longer titles are cut to this many characters.
+    pub max_length: usize,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_length: 50,
+        }
+    }
+}
+
+/// Layout of the focused container under sway or i3.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    pub enabled: bool,
+}
+
+/// The active sway or i3 binding mode, shown first on the bar and in red
+/// outside the default mode.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BindingModeConfig {
+    pub enabled: bool,
+    /// Leave the block out while in the default mode.
+    pub hide_default: bool,
+}
+
+/// Expand a leading `~/` to the user's home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Path of the config file, honouring `XDG_CONFIG_HOME`.
+pub fn config_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("rocketbar").join("config.toml"))
+}
+
+/// Parse config text, collecting every problem rather than stopping at the
+/// first. Each offending line (or whole table, for a bad header) is commented
+/// out and the text parsed again, so the returned config keeps every valid
+/// setting.
+pub fn parse(text: &str) -> (Config, Vec<toml::de::Error>) {
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let mut errors = Vec::new();
+    for _ in 0..=lines.len() {
+        let current = lines.join("\n");
+        let err = match toml::from_str::<Config>(&current) {
+            Ok(config) => return (config, errors),
+            Err(err) => err,
+        };
+        let line = err
+            .span()
+            .map(|span| current[..span.start].matches('\n').count());
+        errors.push(err);
+        if !line.is_some_and(|line| disable_line(&mut lines, line)) {
+            break;
+        }
+    }
+    (Config::default(), errors)
+}
+
+/// Override settings from the environment, for one-off runs and containers:
+///
+/// - `ROCKETBAR_INTERVAL`: `output.interval`
+/// - `ROCKETBAR_WIFI_IFACE`: `net.wifi_interface`
+///
+/// Values that do not parse are reported and ignored.
+pub fn apply_env(config: &mut Config) {
+    if let Ok(value) = env::var("ROCKETBAR_INTERVAL") {
+        match value.trim().parse() {
+            Ok(interval) => config.output.interval = interval,
+            Err(_) => eprintln!("rocketbar: ROCKETBAR_INTERVAL: not a number of seconds: {value}"),
+        }
+    }
+    if let Ok(value) = env::var("ROCKETBAR_WIFI_IFACE") {
+        if value.is_empty() {
+            eprintln!("rocketbar: ROCKETBAR_WIFI_IFACE: empty interface name");
+        } else {
+            config.net.wifi_interface = value;
+        }
+    }
+}
+
+/// Comment out `line`, or the whole table when it is a `[header]`. Returns
+/// false when there was nothing left to disable.
+fn disable_line(lines: &mut [String], line: usize) -> bool {
+    let Some(first) = lines.get(line) else {
+        return false;
+    };
+    if first.trim_start().starts_with('#') {
+        return false;
+    }
+    let end = if first.trim_start().starts_with('[') {
+        lines[line + 1..]
+            .iter()
+            .position(|l| l.trim_start().starts_with('['))
+            .map_or(lines.len(), |i| line + 1 + i)
+    } else {
+        line + 1
+    };
+    for l in &mut lines[line..end] {
+        l.insert(0, '#');
+    }
+    true
+}
+
+/// Load the config file, falling back to defaults when it is missing, and
+/// apply environment overrides. Invalid settings are reported and skipped;
+/// see `rocketbar --check-config`.
+pub fn load() -> Config {
+    let mut config = load_file();
+    apply_env(&mut config);
+    config
+}
+
+fn load_file() -> Config {
+    let Some(path) = config_path() else {
+        return Config::default();
+    };
+    let Ok(data) = fs::read_to_string(&path) else {
+        return Config::default();
+    };
+    let (config, errors) = parse(&data);
+    for e in &errors {
+        eprintln!(
+            "rocketbar: {}: {}",
+            path.display(),
+            e.to_string().trim_end()
+        );
+    }
+    config
+}
+
+/// Watch the config file and swap in the new config whenever it is written.
+/// A file with errors is reported and the running config kept. The IPC
+/// socket path is only read at startup.
+pub fn spawn_watcher(config: SharedConfig, pair: Arc<(Mutex<bool>, Condvar)>) {
+    let Some(path) = config_path() else {
+        return;
+    };
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return;
+    };
+    let name = name.to_owned();
+    let inotify = match Inotify::init(InitFlags::IN_CLOEXEC) {
+        Ok(inotify) => inotify,
+        Err(e) => {
+            eprintln!("rocketbar: inotify: {e}");
+            return;
+        }
+    };
+    let flags = AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO;
+    // Without a config directory there is no config to reload, which is
+    // the normal case rather than a problem worth reporting.
+    match inotify.add_watch(dir, flags) {
+        Ok(_) => {}
+        Err(Errno::ENOENT) => return,
+        Err(e) => {
+            eprintln!("rocketbar: cannot watch {}: {e}", dir.display());
+            return;
+        }
+    }
+
+    thread::spawn(move || {
+        while let Ok(events) = inotify.read_events() {
+            if !events.iter().any(|e| e.name.as_ref() == Some(&name)) {
+                continue;
+            }
+            let Ok(data) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let (mut new_config, errors) = parse(&data);
+            if errors.is_empty() {
+                apply_env(&mut new_config);
+                *config.write().unwrap() = new_config;
+                crate::wake(&pair);
+            } else {
+                for e in &errors {
+                    eprintln!(
+                        "rocketbar: {}: {}",
+                        path.display(),
+                        e.to_string().trim_end()
+                    );
+                }
+                eprintln!("rocketbar: keeping the previous config");
+            }
+        }
+    });
+}
+
+/// Validate the config file for `--check-config`, printing every problem.
+/// Returns whether the file is valid.
+pub fn check() -> bool {
+    let Some(path) = config_path() else {
+        eprintln!("rocketbar: cannot locate the config directory");
+        return false;
+    };
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("{}: not found, using defaults", path.display());
+            return true;
+        }
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return false;
+        }
+    };
+    let (_, errors) = parse(&data);
+    for e in &errors {
+        eprintln!("{}: {}", path.display(), e.to_string().trim_end());
+    }
+    if errors.is_empty() {
+        println!("{}: ok", path.display());
+    } else {
+        eprintln!("{}: {} problem(s) found", path.display(), errors.len());
+    }
+    errors.is_empty()
+}
@@ -0,0 +1,81 @@
+//! `--doctor`: probe each data source the blocks depend on and report which
+//! ones work, since a block whose source is missing is simply left out.
+
+use crate::COMMAND_TIMEOUT;
+use crate::blocks::brightness;
+use crate::command;
+use crate::config::Config;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Print a PASS/FAIL line per data source. Returns whether all of them
+/// passed.
+pub fn run(config: &Config) -> bool {
+    let mut checks: Vec<(String, Result<String, String>)> =
+        vec![("backlight".into(), backlight()), ("pactl".into(), pactl())];
+    let net = &config.net;
+    for (kind, iface) in [
+        ("vpn", &net.vpn_interface),
+        ("ethernet", &net.eth_interface),
+        ("wifi", &net.wifi_interface),
+    ] {
+        checks.push((format!("{kind} interface"), interface(iface)));
+    }
+    checks.push(("fan".into(), fan()));
+    checks.push(("nordvpn".into(), nordvpn()));
+
+    let width = checks.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut passed = true;
+    for (name, result) in &checks {
+        match result {
+            Ok(detail) => println!("PASS  {name:width$}  {detail}"),
+            Err(reason) => {
+                passed = false;
+                println!("FAIL  {name:width$}  {reason}");
+            }
+        }
+    }
+    passed
+}
+
+fn backlight() -> Result<String, String> {
+    let Some(dir) = brightness::device() else {
+        return Err("no device in /sys/class/backlight".to_string());
+    };
+    let path = dir.display();
+    brightness::get_brightness()
+        .map(|level| format!("{path}: {level:.0}%"))
+        .map_err(|e| format!("{path}: {e}"))
+}
+
+fn pactl() -> Result<String, String> {
+    crate::get_volume()
+        .map(|volume| format!("default sink at {volume}%"))
+        .ok_or_else(|| "cannot read the default sink volume".to_string())
+}
+
+fn interface(iface: &str) -> Result<String, String> {
+    let dir = Path::new("/sys/class/net").join(iface);
+    if !dir.exists() {
+        return Err(format!("{iface}: no such interface"));
+    }
+    let state = fs::read_to_string(dir.join("operstate")).unwrap_or_default();
+    Ok(format!("{iface}: {}", state.trim()))
+}
+
+fn fan() -> Result<String, String> {
+    crate::get_fan_speed()
+        .map(|rpm| format!("{rpm} RPM"))
+        .map_err(|e| format!("hwmon fan input: {e}"))
+}
+
+fn nordvpn() -> Result<String, String> {
+    command::run_with_timeout(Command::new("nordvpn").arg("--version"), COMMAND_TIMEOUT)
+        .map(|version| version.lines().next().unwrap_or_default().to_string())
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => "not found in PATH".to_string(),
+            _ => e.to_string(),
+        })
+}
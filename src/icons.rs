@@ -0,0 +1,104 @@
+//! Plain text stand-ins for the Nerd Font glyphs blocks are drawn with, for
+//! bars and terminals without such a font.
+
+use crate::block::Block;
+use crate::blocks::battery;
+use crate::config::IconSet;
+
+/// Labels replacing the icon of each built-in block.
+const LABELS: &[(&str, &str)] = &[
+    ("battery", "bat"),
+    ("binding_mode", "mode"),
+    ("brightness", "bri"),
+    ("caffeine", "caf"),
+    ("calendar", "cal"),
+    ("clipboard", "clip"),
+    ("clock", ""),
+    ("connectivity", "net"),
+    ("cpu", "cpu"),
+    ("docker", "dock"),
+    ("gammastep", "night"),
+    ("git", "git"),
+    ("hottest", "hot"),
+    ("journal", "log"),
+    ("kbd_backlight", "kbd"),
+    ("latency", "ping"),
+    ("layout", "lay"),
+    ("load", "load"),
+    ("mail", "mail"),
+    ("memory", "mem"),
+    ("net", "net"),
+    ("nvme", "nvme"),
+    ("pipewire", "pw"),
+    ("power_profile", "pwr"),
+    ("public_ip", "ip"),
+    ("raid", "raid"),
+    ("recording", "rec"),
+    ("route", "via"),
+    ("sessions", "usr"),
+    ("smart", "smart"),
+    ("storage", "disk"),
+    ("sun", "sun"),
+    ("swap", "swap"),
+    ("tasks", "todo"),
+    ("temperature", "temp"),
+    ("thermal", "temp"),
+    ("timer", "timer"),
+    ("volume", "vol"),
+    ("vpn", "vpn"),
+    ("window", "win"),
+    ("wireguard", "wg"),
+    ("zfs", "zfs"),
+];
+
+/// Glyphs used inside block text, with what they become.
+const TEXT_GLYPHS: &[(char, &str)] = &[
+    ('\u{f062}', "^"),
+    ('\u{f063}', "v"),
+    ('\u{f023}', "vpn"),
+    ('\u{e33e}', "C"),
+    ('\u{e341}', "F"),
+    ('\u{f00c}', "ok"),
+    ('\u{f071}', "!"),
+];
+
+/// Whether `c` is in a private use area, where Nerd Font glyphs live.
+fn is_glyph(c: char) -> bool {
+    matches!(c, '\u{e000}'..='\u{f8ff}' | '\u{f0000}'..='\u{10ffff}')
+}
+
+/// Replace the glyphs of `text` through `TEXT_GLYPHS`, dropping others.
+fn plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !is_glyph(c) {
+            out.push(c);
+        } else if let Some((_, label)) = TEXT_GLYPHS.iter().find(|(glyph, _)| *glyph == c) {
+            out.push_str(label);
+        }
+    }
+    out
+}
+
+/// Swap the glyphs of `blocks` for text when the ASCII set is selected.
+/// Icons become the block's label, or its name for blocks not listed, like
+/// scripts; icons without glyphs, such as configured ones, are kept.
+pub fn apply(blocks: &mut [Block], set: IconSet) {
+    if set == IconSet::NerdFont {
+        return;
+    }
+    for block in blocks {
+        if block.icon.chars().any(is_glyph) {
+            block.icon = if block.icon == battery::CHARGING_ICON {
+                "chg".to_string()
+            } else {
+                LABELS
+                    .iter()
+                    .find(|(name, _)| *name == block.name)
+                    .map_or_else(|| block.name.clone(), |(_, label)| label.to_string())
+            };
+        }
+        block.text = plain(&block.text);
+        block.markup = block.markup.as_deref().map(plain);
+    }
+}
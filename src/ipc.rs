@@ -0,0 +1,247 @@
+//! Control socket for pushing state into the bar from scripts.
+//!
+//! Clients connect to a unix socket and send one command per line, e.g.
+//! `set build "done" 30s`. Every command gets a single `ok` or `error: ...`
+//! line back.
+
+use crate::config::{Config, IpcConfig, SharedConfig};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Text pushed to a named block, optionally cleared after a deadline.
+pub struct Transient {
+    pub text: String,
+    pub expires: Option<Instant>,
+}
+
+/// Countdown started with `timer start`.
+pub struct Timer {
+    pub deadline: Instant,
+    /// Set once the expiry command has been run.
+    pub fired: bool,
+}
+
+/// State that IPC commands act on.
+#[derive(Default)]
+pub struct IpcState {
+    pub transients: Mutex<BTreeMap<String, Transient>>,
+    pub timer: Mutex<Option<Timer>>,
+    /// Group visibility set at runtime, taking precedence over the config.
+    pub group_overrides: Mutex<HashMap<String, bool>>,
+    /// Set by `net reset-peaks`; the net block clears its peaks on the next
+    /// redraw.
+    pub reset_net_peaks: AtomicBool,
+}
+
+impl IpcState {
+    /// Drop expired transients and return the remaining ones in name order.
+    pub fn live_transients(&self) -> Vec<(String, String)> {
+        let now = Instant::now();
+        let mut transients = self.transients.lock().unwrap();
+        transients.retain(|_, t| t.expires.is_none_or(|at| at > now));
+        transients
+            .iter()
+            .map(|(name, t)| (name.clone(), t.text.clone()))
+            .collect()
+    }
+}
+
+/// Path of the control socket, defaulting to `$XDG_RUNTIME_DIR/rocketbar.sock`.
+pub fn socket_path(cfg: &IpcConfig) -> PathBuf {
+    if let Some(path) = &cfg.socket {
+        return crate::config::expand_home(path);
+    }
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("rocketbar.sock"),
+        _ => env::temp_dir().join("rocketbar.sock"),
+    }
+}
+
+/// Bind the control socket and serve commands on a background thread.
+pub fn spawn_listener(
+    path: PathBuf,
+    config: SharedConfig,
+    state: Arc<IpcState>,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+) -> std::io::Result<()> {
+    if UnixStream::connect(&path).is_ok() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            format!("{} is in use by another instance", path.display()),
+        ));
+    }
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            // Each client gets its own thread, so one that connects and never
+            // sends a line cannot lock everyone else out.
+            let config = Arc::clone(&config);
+            let state = Arc::clone(&state);
+            let pair = Arc::clone(&pair);
+            thread::spawn(move || serve(&stream, &config, &state, &pair));
+        }
+    });
+    Ok(())
+}
+
+/// Answer the commands of one client until it disconnects.
+fn serve(
+    stream: &UnixStream,
+    config: &SharedConfig,
+    state: &IpcState,
+    pair: &Arc<(Mutex<bool>, Condvar)>,
+) {
+    let reader = BufReader::new(stream);
+    let mut writer = stream;
+    for line in reader.lines().map_while(Result::ok) {
+        let result = handle_command(&line, &config.read().unwrap(), state);
+        let reply = match result {
+            Ok(()) => {
+                crate::wake(pair);
+                "ok".to_string()
+            }
+            Err(e) => format!("error: {e}"),
+        };
+        if writeln!(writer, "{reply}").is_err() {
+            break;
+        }
+    }
+}
+
+/// Parse and apply a single command line.
+fn handle_command(line: &str, config: &Config, state: &IpcState) -> Result<(), String> {
+    let args = split_args(line)?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["set", name, text] => set_transient(state, name, text, None),
+        ["set", name, text, ttl] => set_transient(state, name, text, Some(parse_duration(ttl)?)),
+        ["clear", name] => {
+            state.transients.lock().unwrap().remove(*name);
+            Ok(())
+        }
+        ["timer", "start", duration] => {
+            let timer = Timer {
+                deadline: deadline(parse_duration(duration)?)?,
+                fired: false,
+            };
+            *state.timer.lock().unwrap() = Some(timer);
+            Ok(())
+        }
+        ["timer", "stop"] => {
+            *state.timer.lock().unwrap() = None;
+            Ok(())
+        }
+        ["group", action @ ("show" | "hide" | "toggle"), group] => {
+            let cfg = config
+                .groups
+                .get(*group)
+                .ok_or_else(|| format!("unknown group: {group}"))?;
+            let mut overrides = state.group_overrides.lock().unwrap();
+            let current = overrides.get(*group).copied().unwrap_or(cfg.enabled);
+            let visible = match *action {
+                "show" => true,
+                "hide" => false,
+                _ => !current,
+            };
+            overrides.insert(group.to_string(), visible);
+            Ok(())
+        }
+        ["net", "reset-peaks"] => {
+            state.reset_net_peaks.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        [] => Err("empty command".to_string()),
+        [cmd, ..] => Err(format!("unknown command or arguments: {cmd}")),
+    }
+}
+
+fn set_transient(
+    state: &IpcState,
+    name: &str,
+    text: &str,
+    ttl: Option<Duration>,
+) -> Result<(), String> {
+    let transient = Transient {
+        text: text.to_string(),
+        expires: ttl.map(deadline).transpose()?,
+    };
+    state
+        .transients
+        .lock()
+        .unwrap()
+        .insert(name.to_string(), transient);
+    Ok(())
+}
+
+/// The instant `duration` from now, or an error when that is too far off to
+/// represent.
+fn deadline(duration: Duration) -> Result<Instant, String> {
+    Instant::now()
+        .checked_add(duration)
+        .ok_or_else(|| "invalid duration".to_string())
+}
+
+/// Parse a duration like `90`, `90s`, `25m` or `1h`; bare numbers are seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, scale) = match s.chars().last() {
+        Some('s') => (&s[..s.len() - 1], 1),
+        Some('m') => (&s[..s.len() - 1], 60),
+        Some('h') => (&s[..s.len() - 1], 3600),
+        _ => (s, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("invalid duration: {s}"))
+}
+
+/// Split a command line into words, honouring double quotes and backslash
+/// escapes.
+fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut in_quotes = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_word = true;
+            }
+            '\\' => {
+                current.push(chars.next().ok_or("trailing backslash")?);
+                in_word = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quote".to_string());
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
+}
@@ -1,16 +1,56 @@
 #![allow(dead_code)]
 
+mod block;
+mod blocks;
+mod cache;
+mod click;
+mod command;
+mod config;
+mod doctor;
+mod icons;
+mod ipc;
+mod metrics;
+mod output;
+mod power;
+mod route;
+mod trend;
+mod watchdog;
+mod wm;
+
+use block::Block;
+use blocks::{
+    battery, binding_mode, brightness, caffeine, calendar, clipboard, clock, connectivity, cpu,
+    docker, gammastep, git, journal, kbd_backlight, layout, mail, memory, nvme, pipewire,
+    power_profile, public_ip, raid, recording, render_bar, render_level, script, sessions, smart,
+    sun, swap, tasks, temperature, threshold_color, timer, window, wireguard, zfs,
+};
+use cache::BlockCache;
 use chrono::Local;
+use click::ClickState;
+use config::{
+    BlinkConfig, Config, DiskDisplay, NumberStyle, RateUnit, SharedConfig, UnitBase, Warmup,
+};
+use ipc::IpcState;
+use metrics::MetricsWriter;
+use nix::sys::signal::{SigSet, Signal};
+use nix::sys::statvfs::statvfs;
+use output::{Output, OutputFormat};
 use regex::Regex;
-use serde_json::json;
+use std::collections::HashMap;
+use std::env;
 use std::error::Error;
 use std::fs::{self, read_to_string};
 use std::io::{BufRead, BufReader};
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Condvar, Mutex};
+use std::net::IpAddr;
+use std::path::Path;
+use std::process::{Command, ExitCode, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use sysinfo::{Components, Disks, Networks, System};
+use trend::{Trend, Trends};
+use watchdog::Heartbeat;
 
 const BLACK: &str = "#15161E";
 const RED: &str = "#f7768e";
@@ -20,18 +60,211 @@ const BLUE: &str = "#7aa2f7";
 const MAGENTA: &str = "#bb9af7";
 const CYAN: &str = "#7dcfff";
 const WHITE: &str = "#a9b1d6";
+const DIM: &str = "#565f89";
+const SURFACE: &str = "#292e42";
 
 const WIFI_INTERFACE: &str = "wlp2s0";
 const VPN_INTERFACE: &str = "nordlynx";
 const ETH_INTERFACE: &str = "enp3s0f0";
 
+/// The preferred backlight; another one is used when it does not exist.
+const BACKLIGHT_DEVICE: &str = "acpi_video0";
+const PULSE_SINK: &str = "@DEFAULT_SINK@";
+
+/// How long `--once` waits for background values before printing.
+const ONCE_SETTLE: Duration = Duration::from_millis(500);
+
+/// How long helper programs like pactl or nordvpn may take before they are
+/// killed, so a hung one cannot freeze the bar.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Byte counters of an interface shown by the net block, turned into rates.
 struct NetTracker {
+    iface: String,
     last_up: u64,
     last_down: u64,
-    last_time: std::time::Instant,
+    last_time: Instant,
+    /// Smoothed rates in bytes per second.
+    rate_up: f32,
+    rate_down: f32,
+    /// Highest unsmoothed rates seen on `iface` since the last reset.
+    peak_up: f32,
+    peak_down: f32,
+    /// Whether the rates come from two samples yet.
+    warm: bool,
 }
-/// Read integer from a file, useful for fan speed and other metrics.
 
+impl NetTracker {
+    fn new() -> Self {
+        Self {
+            iface: String::new(),
+            last_up: 0,
+            last_down: 0,
+            last_time: Instant::now(),
+            rate_up: 0.0,
+            rate_down: 0.0,
+            peak_up: 0.0,
+            peak_down: 0.0,
+            warm: false,
+        }
+    }
+
+    /// Feed the counters of `iface` and return the upload and download rates,
+    /// as an exponential moving average weighting the newest sample by
+    /// `alpha`. Switching interfaces starts over from zero.
+    fn update(&mut self, iface: &str, up: u64, down: u64, alpha: f32) -> (f32, f32) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_time).as_secs_f32();
+        if self.iface != iface {
+            self.iface = iface.to_string();
+            self.rate_up = 0.0;
+            self.rate_down = 0.0;
+            self.warm = false;
+            self.reset_peaks();
+        } else if elapsed > 0.0 {
+            self.warm = true;
+            let raw_up = up.saturating_sub(self.last_up) as f32 / elapsed;
+            let raw_down = down.saturating_sub(self.last_down) as f32 / elapsed;
+            self.rate_up = alpha * raw_up + (1.0 - alpha) * self.rate_up;
+            self.rate_down = alpha * raw_down + (1.0 - alpha) * self.rate_down;
+            self.peak_up = self.peak_up.max(raw_up);
+            self.peak_down = self.peak_down.max(raw_down);
+        }
+        self.last_up = up;
+        self.last_down = down;
+        self.last_time = now;
+        (self.rate_up, self.rate_down)
+    }
+
+    fn reset_peaks(&mut self) {
+        self.peak_up = 0.0;
+        self.peak_down = 0.0;
+    }
+}
+
+/// Counts timer ticks so alerting blocks can animate and the clock rotate.
+/// Redraws prompted by clicks, IPC or pollers do not count, so the pace
+/// stays the same however busy the bar is.
+#[derive(Default)]
+struct AnimationState {
+    tick: u64,
+    /// When the next tick is due; unset until the render loop schedules it.
+    due: Option<Instant>,
+}
+
+impl AnimationState {
+    /// Count a tick if one is due.
+    fn advance(&mut self) {
+        if self.due.is_some_and(|due| Instant::now() >= due) {
+            self.tick += 1;
+            self.due = None;
+        }
+    }
+
+    /// Have the next tick come `interval` from now, unless one is already
+    /// scheduled.
+    fn schedule(&mut self, interval: Duration) {
+        self.due.get_or_insert_with(|| Instant::now() + interval);
+    }
+
+    /// Color for an alerting block: alternates between the two blink colors
+    /// every tick, or stays on the first one when blinking is disabled.
+    fn alert_color<'a>(&self, blink: &'a BlinkConfig) -> &'a str {
+        if blink.enabled && self.tick % 2 == 1 {
+            &blink.colors[1]
+        } else {
+            &blink.colors[0]
+        }
+    }
+}
+
+/// Latest values produced by the background pollers.
+#[derive(Default)]
+struct Polled {
+    containers: Arc<Mutex<Option<u32>>>,
+    tasks: Arc<Mutex<Option<u32>>>,
+    mail: Arc<Mutex<Option<u32>>>,
+    journal: Arc<Mutex<Option<u32>>>,
+    calendar: Arc<Mutex<Option<Vec<calendar::Event>>>>,
+    /// Bytes on the clipboard.
+    clipboard: Arc<Mutex<Option<usize>>>,
+    gammastep: Arc<Mutex<Option<gammastep::State>>>,
+    recording: Arc<Mutex<Option<bool>>>,
+    git: Arc<Mutex<Option<Vec<git::GitStatus>>>>,
+    cpu: Arc<Mutex<Option<cpu::CpuUsage>>>,
+    swap: Arc<Mutex<Option<swap::SwapRate>>>,
+    temperature: Arc<Mutex<Option<f32>>>,
+    hottest: Arc<Mutex<Option<temperature::Reading>>>,
+    vpn_country: Arc<Mutex<Option<String>>>,
+    wireguard: Arc<Mutex<Option<Vec<wireguard::Tunnel>>>>,
+    online: Arc<Mutex<Option<bool>>>,
+    /// The last measurement, itself `None` when the connect failed.
+    latency: Arc<Mutex<Option<Option<f32>>>>,
+    sessions: Arc<Mutex<Option<sessions::Sessions>>>,
+    zfs: Arc<Mutex<Option<Vec<zfs::Pool>>>>,
+    pipewire: Arc<Mutex<Option<pipewire::Graph>>>,
+    power_profile: Arc<Mutex<Option<String>>>,
+    smart: Arc<Mutex<Option<Vec<smart::Drive>>>>,
+    public_ip: Arc<Mutex<Option<Vec<IpAddr>>>>,
+    scripts: Arc<BlockCache>,
+    window: Arc<Mutex<Option<String>>>,
+    layout: Arc<Mutex<Option<String>>>,
+    binding_mode: Arc<Mutex<Option<String>>>,
+}
+
+/// Whether a `pactl subscribe` line may mean the volume changed: a sink
+/// changed, or a server or card event, which is how a switch of the default
+/// sink shows up. Streams (`sink-input`) changing do not count.
+fn affects_volume(event: &str) -> bool {
+    let event = event.to_lowercase();
+    if event.contains("sink-input") {
+        return false;
+    }
+    (event.contains("sink") && event.contains("change"))
+        || event.contains("server")
+        || event.contains("card")
+}
+
+/// Wake the render loop so it prints a fresh status line.
+fn wake(pair: &(Mutex<bool>, Condvar)) {
+    let (lock, cvar) = pair;
+    let mut notified = lock.lock().unwrap();
+    *notified = true;
+    cvar.notify_one();
+}
+
+/// Call `poll` with the current config on a background thread, storing the
+/// result in `slot` and waking the render loop whenever it changes. The
+/// config is read afresh every cycle so reloads apply from the next poll.
+fn spawn_poller<T, F>(
+    slot: Arc<Mutex<Option<T>>>,
+    config: SharedConfig,
+    pair: Arc<(Mutex<bool>, Condvar)>,
+    interval: fn(&Config) -> u64,
+    mut poll: F,
+) where
+    T: PartialEq + Send + 'static,
+    F: FnMut(&Config) -> Option<T> + Send + 'static,
+{
+    thread::spawn(move || {
+        loop {
+            let config = config.read().unwrap().clone();
+            let value = poll(&config);
+            let changed = {
+                let mut lock = slot.lock().unwrap();
+                let changed = *lock != value;
+                *lock = value;
+                changed
+            };
+            if changed {
+                wake(&pair);
+            }
+            thread::sleep(power::paced(interval(&config), &config));
+        }
+    });
+}
+
+/// Read integer from a file, useful for fan speed and other metrics.
 fn read_int_from_file(path: &str) -> Result<u32, Box<dyn Error>> {
     let data = fs::read_to_string(path)?;
     let number = data.trim().parse::<u32>()?;
@@ -43,7 +276,7 @@ fn read_load_avg(path: &str) -> Result<(f32, f32, f32), Box<dyn Error>> {
     let data = fs::read_to_string(path)?;
     let numbers: Vec<&str> = data.split_whitespace().collect();
     let load1 = numbers
-        .get(0)
+        .first()
         .ok_or("Missing 01 load avg")?
         .parse::<f32>()?;
     let load2 = numbers
@@ -58,10 +291,10 @@ fn read_load_avg(path: &str) -> Result<(f32, f32, f32), Box<dyn Error>> {
 }
 
 /// Convert bytes into a human-readable format (e.g., MB, GB).
-fn readable_bytes(mut num: f32) -> String {
+fn readable_bytes(mut num: f32, numbers: &NumberStyle) -> String {
     for unit in ["B", "KB", "MB", "GB", "TB", "PB"].iter() {
         if num < 1024.0 {
-            return format!("{num:.2}{unit}");
+            return format!("{}{unit}", numbers.format(num, 2));
         } else {
             num /= 1024.0;
         }
@@ -69,29 +302,72 @@ fn readable_bytes(mut num: f32) -> String {
     "ERROR".to_string()
 }
 
+/// Make a rate in bytes per second readable, e.g. `4.20MB/s` or `33.60Mb/s`.
+fn readable_rate(bytes: f32, unit: RateUnit, base: UnitBase, numbers: &NumberStyle) -> String {
+    let (mut num, units) = match unit {
+        RateUnit::Bytes => (bytes, ["B", "KB", "MB", "GB", "TB", "PB"]),
+        RateUnit::Bits => (bytes * 8.0, ["b", "Kb", "Mb", "Gb", "Tb", "Pb"]),
+    };
+    let base = base.0 as f32;
+    for unit in units {
+        if num < base {
+            return format!("{}{unit}/s", numbers.format(num, 2));
+        }
+        num /= base;
+    }
+    "ERROR".to_string()
+}
+
+/// Make a rate short, e.g. `1.2M` or `340K`, with one decimal only below
+/// ten and a trailing `b` when counting bits. Both are judged after
+/// rounding, so 9.97K shows as `10K` and 1023.9B as `1.0K`.
+fn compact_rate(bytes: f32, unit: RateUnit, base: UnitBase, numbers: &NumberStyle) -> String {
+    let (mut num, suffix) = match unit {
+        RateUnit::Bytes => (bytes, ""),
+        RateUnit::Bits => (bytes * 8.0, "b"),
+    };
+    let base = base.0 as f32;
+    for prefix in ["", "K", "M", "G", "T", "P"] {
+        let precision = if !prefix.is_empty() && numbers.rounding.apply(num, 1) < 10.0 {
+            1
+        } else {
+            0
+        };
+        if numbers.rounding.apply(num, precision) < f64::from(base) {
+            return format!("{}{prefix}{suffix}", numbers.format(num, precision));
+        }
+        num /= base;
+    }
+    "ERROR".to_string()
+}
+
 /// Fetch current system volume using `pactl`.
 fn get_volume() -> Option<u32> {
-    let output = Command::new("pactl")
-        .args(&["get-sink-volume", "@DEFAULT_SINK@"])
-        .output()
-        .ok()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = command::run_with_timeout(
+        Command::new("pactl").args(["get-sink-volume", PULSE_SINK]),
+        COMMAND_TIMEOUT,
+    )
+    .ok()?;
     let re = Regex::new(r"/\s*(\d+)%").unwrap();
     re.captures(&stdout)
         .and_then(|caps| caps.get(1))
         .and_then(|m| m.as_str().parse::<u32>().ok())
 }
 
-/// Format the volume into a human-readable string with an icon.
-fn format_volume(vol: u32) -> String {
-    let icon = match vol {
-        0 => "",
-        //1..=30 => "",
-        //31..=70 => "",
-        _ => "",
-    };
-    format!("{}  {}", icon, vol)
+/// Built-in volume icons by level.
+const VOLUME_RAMP: &[(f32, &str)] = &[(0.0, ""), (1.0, ""), (31.0, ""), (71.0, "")];
+
+/// Icon for an interface found by scanning: wifi for wireless devices, a
+/// lock for tunnels, ethernet otherwise.
+fn link_icon(iface: &str) -> &'static str {
+    let dir = Path::new("/sys/class/net").join(iface);
+    if dir.join("wireless").exists() {
+        ""
+    } else if dir.join("tun_flags").exists() || iface.starts_with("wg") {
+        ""
+    } else {
+        ""
+    }
 }
 
 /// Check if a network interface is enabled.
@@ -107,15 +383,6 @@ fn check_interface_up(iface: &str) -> bool {
         == "up"
 }
 
-/// Get the current brightness level.
-fn get_brightness() -> Result<u32, Box<dyn Error>> {
-    let data0 = read_to_string("/sys/class/backlight/acpi_video0/brightness")?;
-    let data1 = read_to_string("/sys/class/backlight/acpi_video0/max_brightness")?;
-    let brightness = data0.trim().parse::<u32>()?;
-    let brightness_max = data1.trim().parse::<u32>()?;
-    Ok(((brightness as f32 / brightness_max as f32) * 100.0) as u32)
-}
-
 /// Get the fan speed (in RPM) from system sensors.
 fn get_fan_speed() -> Result<u32, Box<dyn Error>> {
     let path = "/sys/class/hwmon/hwmon0/device/fan1_input";
@@ -125,195 +392,1115 @@ fn get_fan_speed() -> Result<u32, Box<dyn Error>> {
 
 /// Get the system's IP address.
 fn get_ip_address() -> Result<Vec<String>, Box<dyn Error>> {
-    let output = Command::new("ip").arg("a").output()?;
-    let ip_address = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let ip_address = command::run_with_timeout(Command::new("ip").arg("a"), COMMAND_TIMEOUT)?;
     let mut ip = Vec::new();
     for x in ip_address.lines() {
         if x.contains("inet ") && !x.contains("127.0.0.1") {
             ip.push(format!(
                 "{} {}",
-                x.split_whitespace().into_iter().last().unwrap().to_string(),
-                x.split_whitespace().into_iter().nth(1).unwrap().to_string()
+                x.split_whitespace().last().unwrap(),
+                x.split_whitespace().nth(1).unwrap()
             ))
         }
     }
     Ok(ip)
 }
 
-/// Print the system status as JSON.
-fn print_status(sys: &mut System, volume: u32, tracker: &mut NetTracker) {
+/// One line per mounted disk with its usage, for the storage tooltip.
+fn disk_summary(disks: &Disks, numbers: &NumberStyle) -> String {
+    disks
+        .iter()
+        .filter(|d| d.total_space() > 0)
+        .map(|d| {
+            let used = d.total_space() - d.available_space();
+            format!(
+                "{}  {} / {}",
+                d.mount_point().display(),
+                readable_bytes(used as f32, numbers),
+                readable_bytes(d.total_space() as f32, numbers)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Percentage of inodes in use on the filesystem mounted at `mount`, or None
+/// for filesystems without a fixed inode count, like btrfs.
+fn inode_usage(mount: &str) -> Option<f32> {
+    let stat = statvfs(mount).ok()?;
+    if stat.files() == 0 {
+        return None;
+    }
+    let used = stat.files() - stat.files_free();
+    Some(used as f32 / stat.files() as f32 * 100.0)
+}
+
+/// Whether a block may be shown, i.e. no group containing it is disabled,
+/// either in the config or by an IPC override.
+fn block_shown(config: &Config, ipc: &IpcState, name: &str) -> bool {
+    let overrides = ipc.group_overrides.lock().unwrap();
+    config.groups.iter().all(|(group, cfg)| {
+        !cfg.blocks.iter().any(|b| b == name)
+            || overrides.get(group).copied().unwrap_or(cfg.enabled)
+    })
+}
+
+/// Whether any block is turned on and not hidden by a group.
+fn any_block_enabled(config: &Config) -> bool {
+    let ipc = IpcState::default();
+    config
+        .enabled_blocks()
+        .into_iter()
+        .any(|name| block_shown(config, &ipc, name))
+}
+
+/// How long a tick waits for the system sources before drawing with the
+/// last values of those still being read.
+const SOURCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// What the blocks reading the system directly need for a tick; sources of
+/// blocks that are off are left empty.
+struct Sources {
+    disks: Arc<Disks>,
+    inodes: Option<f32>,
+    networks: Arc<Networks>,
+    brightness: Option<f32>,
+    kbd_backlight: Option<(String, f32)>,
+    battery: Option<battery::Battery>,
+}
+
+/// One system source, read on a detached thread that outlives the tick if it
+/// has to. A read that hangs keeps its source busy, so no second thread is
+/// started for it until the first returns.
+struct Source<T> {
+    value: Arc<Mutex<Option<T>>>,
+    busy: Arc<AtomicBool>,
+}
+
+impl<T> Default for Source<T> {
+    fn default() -> Self {
+        Source {
+            value: Arc::default(),
+            busy: Arc::default(),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Source<T> {
+    /// Start reading with `read`, counted in `pending` until it finishes,
+    /// unless the last read is still running.
+    fn start(
+        &self,
+        pending: &Arc<(Mutex<usize>, Condvar)>,
+        read: impl FnOnce() -> Option<T> + Send + 'static,
+    ) {
+        if self.busy.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        *pending.0.lock().unwrap() += 1;
+        let value = Arc::clone(&self.value);
+        let busy = Arc::clone(&self.busy);
+        let pending = Arc::clone(pending);
+        thread::spawn(move || {
+            *value.lock().unwrap() = read();
+            busy.store(false, Ordering::Release);
+            let (count, done) = &*pending;
+            *count.lock().unwrap() -= 1;
+            done.notify_all();
+        });
+    }
+
+    /// The result of the last read that finished.
+    fn get(&self) -> Option<T> {
+        self.value.lock().unwrap().clone()
+    }
+}
+
+/// The sources of [`Sources`], kept across ticks.
+#[derive(Default)]
+struct SourceReaders {
+    disks: Source<Arc<Disks>>,
+    inodes: Source<f32>,
+    networks: Source<Arc<Networks>>,
+    brightness: Source<f32>,
+    kbd_backlight: Source<(String, f32)>,
+    battery: Source<battery::Battery>,
+}
+
+/// Read the sources of shown blocks in parallel and wait up to
+/// [`SOURCE_TIMEOUT`] for them. One that is slower, like a hung network
+/// mount while listing disks, is left running and its last value used, so
+/// it does not hold up the tick. The blocks are still built from them in
+/// order afterwards.
+fn read_sources(config: &Config, shown: &dyn Fn(&str) -> bool, readers: &SourceReaders) -> Sources {
+    let storage = config.storage.enabled && shown("storage");
+    let net = config.net.enabled && shown("net");
+    let brightness = shown("brightness");
+    let kbd_backlight = shown("kbd_backlight");
+    let battery = config.battery.enabled && shown("battery");
+
+    let pending = Arc::new((Mutex::new(0), Condvar::new()));
+    if storage {
+        readers.disks.start(&pending, || {
+            Some(Arc::new(Disks::new_with_refreshed_list()))
+        });
+        let mount = config.storage.mount.clone();
+        readers.inodes.start(&pending, move || inode_usage(&mount));
+    }
+    if net {
+        readers.networks.start(&pending, || {
+            Some(Arc::new(Networks::new_with_refreshed_list()))
+        });
+    }
+    if brightness {
+        readers
+            .brightness
+            .start(&pending, || brightness::get_brightness().ok());
+    }
+    if kbd_backlight {
+        readers
+            .kbd_backlight
+            .start(&pending, kbd_backlight::get_level);
+    }
+    if battery {
+        let device = config.battery.device.clone();
+        readers
+            .battery
+            .start(&pending, move || battery::get_battery(&device));
+    }
+    let (count, done) = &*pending;
+    let _ = done
+        .wait_timeout_while(count.lock().unwrap(), SOURCE_TIMEOUT, |count| *count > 0)
+        .unwrap();
+
+    Sources {
+        disks: storage
+            .then(|| readers.disks.get())
+            .flatten()
+            .unwrap_or_else(|| Arc::new(Disks::new())),
+        inodes: storage.then(|| readers.inodes.get()).flatten(),
+        networks: net
+            .then(|| readers.networks.get())
+            .flatten()
+            .unwrap_or_else(|| Arc::new(Networks::new())),
+        brightness: brightness.then(|| readers.brightness.get()).flatten(),
+        kbd_backlight: kbd_backlight.then(|| readers.kbd_backlight.get()).flatten(),
+        battery: battery.then(|| readers.battery.get()).flatten(),
+    }
+}
+
+/// Collect the blocks to show this tick. Also returns every block built, before
+/// `hide_when` and merges, for the metrics outputs.
+#[allow(clippy::too_many_arguments)]
+fn build_status(
+    sys: &mut System,
+    config: &Config,
+    volume: u32,
+    polled: &Polled,
+    ipc: &IpcState,
+    clicks: &ClickState,
+    anim: &mut AnimationState,
+    trackers: &mut HashMap<String, NetTracker>,
+    trends: &mut Trends,
+    timer_tick: bool,
+    held: &mut HashMap<String, Vec<Block>>,
+    readers: &SourceReaders,
+) -> (Vec<Block>, Vec<Block>) {
+    anim.advance();
+    let shown = |name: &str| block_shown(config, ipc, name);
+    // Event-only blocks keep what they were last built as on a timer tick.
+    let reuse = |name: &str| {
+        timer_tick
+            .then(|| held.get(name))
+            .flatten()
+            .filter(|_| config.output.event_only.iter().any(|b| b == name))
+            .cloned()
+    };
     let now = Local::now();
-    let time = now.format("%H:%M:%S").to_string();
-    let day = now.format("%A, %d %B %Y").to_string();
 
-    sys.refresh_cpu_all();
     sys.refresh_memory();
 
-    let disks = Disks::new_with_refreshed_list();
-    let components = Components::new_with_refreshed_list();
-    let networks = Networks::new_with_refreshed_list();
-    let mut status = Vec::new();
-
-    // Network
-    // let wifi_up = check_interface_up(WIFI_INTERFACE);
-    // let vpn_up = check_interface_enable(VPN_INTERFACE);
-    // let ethernet_up = check_interface_up(ETH_INTERFACE);
-    //
-    // let now = std::time::Instant::now();
-    // let elapsed = now.duration_since(tracker.last_time).as_secs_f32();
-    //
-    // if vpn_up && ethernet_up {
-    //     if let Some(vpn) = networks.get(VPN_INTERFACE) {
-    //         let current_up = vpn.total_transmitted();
-    //         let current_down = vpn.total_received();
-    //         let rate_up = if elapsed > 0.0 {
-    //             (current_up - tracker.last_up) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         let rate_down = if elapsed > 0.0 {
-    //             (current_down - tracker.last_down) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         tracker.last_up = current_up;
-    //         tracker.last_down = current_down;
-    //         tracker.last_time = now;
-    //
-    //         status.push(json!({
-    //             "full_text": format!("   {}  {}s  {}s",
-    //                 get_country_code().unwrap_or("..".to_string()),
-    //                 readable_bytes(rate_up),
-    //                 readable_bytes(rate_down)),
-    //             "name": "net"
-    //         }));
-    //     }
-    // } else if vpn_up {
-    //     if let Some(vpn) = networks.get(VPN_INTERFACE) {
-    //         let current_up = vpn.total_transmitted();
-    //         let current_down = vpn.total_received();
-    //         let rate_up = if elapsed > 0.0 {
-    //             (current_up - tracker.last_up) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         let rate_down = if elapsed > 0.0 {
-    //             (current_down - tracker.last_down) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         tracker.last_up = current_up;
-    //         tracker.last_down = current_down;
-    //         tracker.last_time = now;
-    //
-    //         status.push(json!({
-    //             "full_text": format!("   {}  {}s  {}s",
-    //                 get_country_code().unwrap_or("..".to_string()),
-    //                 readable_bytes(rate_up),
-    //                 readable_bytes(rate_down)),
-    //             "name": "net"
-    //         }));
-    //     }
-    // } else if ethernet_up {
-    //     if let Some(ethernet) = networks.get(ETH_INTERFACE) {
-    //         let current_up = ethernet.total_transmitted();
-    //         let current_down = ethernet.total_received();
-    //         let rate_up = if elapsed > 0.0 {
-    //             (current_up - tracker.last_up) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         let rate_down = if elapsed > 0.0 {
-    //             (current_down - tracker.last_down) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         tracker.last_up = current_up;
-    //         tracker.last_down = current_down;
-    //         tracker.last_time = now;
-    //
-    //         status.push(json!({
-    //             "full_text": format!("   {}s  {}s",
-    //                 readable_bytes(rate_up),
-    //                 readable_bytes(rate_down)),
-    //             "name": "net",
-    //             "color" : BLUE,
-    //         }));
-    //     }
-    // } else if wifi_up {
-    //     if let Some(wifi) = networks.get(WIFI_INTERFACE) {
-    //         let current_up = wifi.total_transmitted();
-    //         let current_down = wifi.total_received();
-    //         let rate_up = if elapsed > 0.0 {
-    //             (current_up - tracker.last_up) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         let rate_down = if elapsed > 0.0 {
-    //             (current_down - tracker.last_down) as f32 / elapsed
-    //         } else {
-    //             0.0
-    //         };
-    //         tracker.last_up = current_up;
-    //         tracker.last_down = current_down;
-    //         tracker.last_time = now;
-    //
-    //         status.push(json!({
-    //             "full_text": format!("   {}s {}s",
-    //                 readable_bytes(rate_up),
-    //                 readable_bytes(rate_down)),
-    //             "name": "net"
-    //         }));
-    //     }
-    // }
+    let sources = read_sources(config, &shown, readers);
+    let (disks, networks) = (&sources.disks, &sources.networks);
+    let numbers = &config.output.numbers;
+    let mut status: Vec<Block> = Vec::new();
+
+    // Binding mode, leading the bar like i3bar's own indicator
+    if config.binding_mode.enabled
+        && shown("binding_mode")
+        && let Some(mode) = &*polled.binding_mode.lock().unwrap()
+    {
+        let default = mode == binding_mode::DEFAULT;
+        if !(default && config.binding_mode.hide_default) {
+            let block = Block::new("binding_mode", binding_mode::ICON, mode.as_str());
+            status.push(if default { block } else { block.color(RED) });
+        }
+    }
+
+    // Network, on the first interface that is up: the VPN when it is,
+    // drawn over ethernet or wifi, then plain ethernet, then wifi. In auto
+    // mode, every interface that is up or moving data instead.
+    if config.net.enabled && shown("net") {
+        let net = &config.net;
+        if ipc.reset_net_peaks.swap(false, Ordering::Relaxed)
+            | clicks.reset_net_peaks.swap(false, Ordering::Relaxed)
+        {
+            trackers.values_mut().for_each(NetTracker::reset_peaks);
+        }
+        let vpn_up = !net.auto && check_interface_enable(&net.vpn_interface);
+        let links: Vec<(&str, &str)> = if net.auto {
+            let mut names: Vec<&str> = networks
+                .keys()
+                .map(|name| name.as_str())
+                .filter(|name| *name != "lo")
+                .collect();
+            names.sort_unstable();
+            names
+                .into_iter()
+                .map(|name| (name, link_icon(name)))
+                .collect()
+        } else if vpn_up {
+            let icon = if check_interface_up(&net.eth_interface) {
+                ""
+            } else {
+                ""
+            };
+            vec![(net.vpn_interface.as_str(), icon)]
+        } else if check_interface_up(&net.eth_interface) {
+            vec![(net.eth_interface.as_str(), "")]
+        } else if check_interface_up(&net.wifi_interface) {
+            vec![(net.wifi_interface.as_str(), "")]
+        } else {
+            Vec::new()
+        };
+        trackers.retain(|iface, _| links.iter().any(|(link, _)| link == iface));
+        for (iface, icon) in links {
+            let Some(data) = networks.get(iface) else {
+                continue;
+            };
+            let tracker = trackers
+                .entry(iface.to_string())
+                .or_insert_with(NetTracker::new);
+            let (up, down) = tracker.update(
+                iface,
+                data.total_transmitted(),
+                data.total_received(),
+                net.alpha,
+            );
+            // Tunnels report their state as unknown rather than up.
+            if net.auto && !check_interface_up(iface) && up + down == 0.0 {
+                continue;
+            }
+            if !tracker.warm {
+                match config.output.warmup {
+                    Warmup::Off => {}
+                    Warmup::Hide => continue,
+                    Warmup::Placeholder => {
+                        status.push(Block::new("net", icon, "--").instance(iface));
+                        continue;
+                    }
+                }
+            }
+            let rate = |bytes| readable_rate(bytes, net.unit, net.base, numbers);
+            let short = |bytes| compact_rate(bytes, net.unit, net.base, numbers);
+            let idle = up < 1.0 && down < 1.0;
+            let (mut rates, mut markup) = match &net.idle_icon {
+                Some(idle_icon) if idle => (idle_icon.clone(), None),
+                _ if net.compact => (
+                    format!("↓{} ↑{}", short(down), short(up)),
+                    Some(format!(
+                        "<span foreground=\"{}\">↓</span>{} <span foreground=\"{}\">↑</span>{}",
+                        net.down_color,
+                        short(down),
+                        net.up_color,
+                        short(up)
+                    )),
+                ),
+                _ => (format!(" {}  {}", rate(up), rate(down)), None),
+            };
+            if net.show_peaks {
+                let peaks = format!(
+                    " max  {}  {}",
+                    rate(tracker.peak_up),
+                    rate(tracker.peak_down)
+                );
+                rates += &peaks;
+                if let Some(markup) = &mut markup {
+                    *markup += &peaks;
+                }
+            }
+            let prefix = match (vpn_up, net.vpn_country) {
+                (true, true) => {
+                    let country = polled.vpn_country.lock().unwrap().clone();
+                    let country = country.unwrap_or("..".to_string());
+                    format!(" {country} ")
+                }
+                (true, false) => " ".to_string(),
+                (false, _) => String::new(),
+            };
+            let mut block = Block::new("net", icon, format!("{prefix}{rates}"));
+            if let Some(markup) = markup {
+                block = block.markup(format!("{prefix}{markup}"));
+            }
+            if let Some(active_rate) = net.active_rate {
+                let active = up.max(down) > active_rate;
+                block = block.color(if active {
+                    &net.active_color
+                } else {
+                    &net.idle_color
+                });
+            } else if iface == net.eth_interface {
+                block = block.color(BLUE);
+            }
+            // The interface with its addresses, sorted since sysinfo lists
+            // them in no fixed order, and peak rates.
+            let mut addrs: Vec<String> = data
+                .ip_networks()
+                .iter()
+                .map(|network| format!("{}/{}", network.addr, network.prefix))
+                .collect();
+            addrs.sort_unstable();
+            let mut tooltip = vec![iface.to_string()];
+            tooltip.extend(addrs);
+            tooltip.push(format!(
+                "peak ↓{} ↑{}",
+                rate(tracker.peak_down),
+                rate(tracker.peak_up)
+            ));
+            status.push(
+                block
+                    .instance(iface)
+                    .value(up + down)
+                    .tooltip(tooltip.join("\n")),
+            );
+        }
+    }
 
     // Storage
-    //if let Some(disk) = disks.first() {
-    //    status.push(json!({
-    //        "full_text": format!("󰋊 {:4.1}",
-    //            ((disk.total_space() - disk.available_space()) as f32 / disk.total_space() as f32) * 100.0),
-    //        "name": "storage"
-    //    }));
-    //}
+    if config.storage.enabled
+        && shown("storage")
+        && let Some(disk) = disks
+            .iter()
+            .find(|d| d.mount_point() == Path::new(&config.storage.mount) && d.total_space() > 0)
+    {
+        let used_space = disk.total_space() - disk.available_space();
+        let used = used_space as f32 / disk.total_space() as f32;
+        let text = match config.storage.display {
+            DiskDisplay::PercentUsed => config.storage.format.apply(used * 100.0, "%", numbers),
+            DiskDisplay::Free => readable_bytes(disk.available_space() as f32, numbers),
+            DiskDisplay::UsedOfTotal => format!(
+                "{}/{}",
+                readable_bytes(used_space as f32, numbers),
+                readable_bytes(disk.total_space() as f32, numbers)
+            ),
+        };
+        let inodes = sources.inodes;
+        let inode_alert = inodes
+            .zip(config.storage.inode_alert)
+            .is_some_and(|(i, at)| i >= at);
+        let text = match inodes {
+            Some(inodes) if config.storage.inodes || inode_alert => {
+                format!("{text} {inodes:.0}% inodes")
+            }
+            _ => text,
+        };
+        let mut block = Block::new("storage", "󰋊", text)
+            .instance(&config.storage.mount)
+            .percentage(used * 100.0)
+            .tooltip(disk_summary(disks, numbers))
+            .urgent_at(used * 100.0, config.storage.urgent);
+        if inode_alert {
+            block = block.color(RED);
+        }
+        status.push(block);
+    }
+
     // Temperature
-    // if let Some(temp) = components.first() {
-    //     if let Some(temperature) = temp.temperature() {
-    //         status.push(json!({
-    //             "full_text": format!(" {}C", temperature),
-    //             "name": "temperature"
-    //         }));
-    //     }
-    // }
+    let temp_unit = config.output.temperature_unit;
+    let temp_suffix = temperature::suffix(temp_unit);
+    if config.temperature.enabled
+        && shown("temperature")
+        && let Some(temp) = *polled.temperature.lock().unwrap()
+    {
+        let temp = temperature::convert(temp, temp_unit);
+        let text = config.temperature.format.apply(temp, temp_suffix, numbers);
+        let mut block = Block::new("temperature", temperature::ICON, text)
+            .value(temp)
+            .urgent_at(temp, config.temperature.urgent);
+        let cfg = &config.temperature;
+        let (warning, critical) = temperature::thresholds(
+            cfg.warning,
+            cfg.critical,
+            temperature::THRESHOLDS,
+            temp_unit,
+        );
+        if let Some(color) = threshold_color(temp, warning, critical) {
+            block = block.color(color);
+        }
+        status.push(block);
+    }
+
+    // Hottest sensor, labeled
+    if config.hottest.enabled
+        && shown("hottest")
+        && let Some(reading) = &*polled.hottest.lock().unwrap()
+    {
+        let cfg = &config.hottest;
+        let temp = temperature::convert(reading.celsius, temp_unit);
+        let text = cfg.format.apply(temp, temp_suffix, numbers);
+        let mut block = Block::new(
+            "hottest",
+            temperature::ICON,
+            format!("{} {text}", reading.label),
+        )
+        .instance(&reading.label)
+        .value(temp)
+        .urgent_at(temp, cfg.urgent);
+        let (warning, critical) = temperature::thresholds(
+            cfg.warning,
+            cfg.critical,
+            temperature::THRESHOLDS,
+            temp_unit,
+        );
+        if let Some(color) = threshold_color(temp, warning, critical) {
+            block = block.color(color);
+        }
+        status.push(block);
+    }
+
+    // Thermal zone
+    if config.thermal.enabled
+        && shown("thermal")
+        && let Some(temp) = temperature::read_thermal_zone(&config.thermal.zone)
+    {
+        let temp = temperature::convert(temp, temp_unit);
+        let text = config.thermal.format.apply(temp, temp_suffix, numbers);
+        let mut block = Block::new("thermal", temperature::ICON, text)
+            .instance(&config.thermal.zone)
+            .value(temp)
+            .urgent_at(temp, config.thermal.urgent);
+        let cfg = &config.thermal;
+        let (warning, critical) = temperature::thresholds(
+            cfg.warning,
+            cfg.critical,
+            temperature::THRESHOLDS,
+            temp_unit,
+        );
+        if let Some(color) = threshold_color(temp, warning, critical) {
+            block = block.color(color);
+        }
+        status.push(block);
+    }
+
+    // Drive temperature
+    if config.nvme.enabled
+        && shown("nvme")
+        && let Some(temp) = nvme::get_nvme_temperature()
+    {
+        let temp = temperature::convert(temp, temp_unit);
+        let text = config.nvme.format.apply(temp, temp_suffix, numbers);
+        let mut block = Block::new("nvme", nvme::ICON, text)
+            .value(temp)
+            .urgent_at(temp, config.nvme.urgent);
+        let cfg = &config.nvme;
+        let (warning, critical) =
+            temperature::thresholds(cfg.warning, cfg.critical, nvme::THRESHOLDS, temp_unit);
+        if let Some(color) = threshold_color(temp, warning, critical) {
+            block = block.color(color);
+        }
+        status.push(block);
+    }
+
+    // RAID arrays: green while all are clean, red with the rebuild progress
+    // otherwise
+    if config.raid.enabled && shown("raid") {
+        let arrays = raid::get_arrays();
+        if !arrays.is_empty() {
+            let text = arrays
+                .iter()
+                .map(|a| match a.progress {
+                    _ if a.is_clean() => a.name.clone(),
+                    Some(progress) => format!("{} [{}] {progress:.1}%", a.name, a.members),
+                    None => format!("{} [{}]", a.name, a.members),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let clean = arrays.iter().all(raid::Array::is_clean);
+            let block = Block::new("raid", raid::ICON, text);
+            status.push(if clean {
+                block.color(GREEN)
+            } else {
+                block.color(RED)
+            });
+        }
+    }
+
+    // ZFS pools, red while any is not online
+    if config.zfs.enabled
+        && shown("zfs")
+        && let Some(pools) = &*polled.zfs.lock().unwrap()
+        && !pools.is_empty()
+    {
+        let text = pools
+            .iter()
+            .map(|p| {
+                let icon = if p.is_online() {
+                    zfs::ONLINE_ICON
+                } else {
+                    zfs::FAULT_ICON
+                };
+                match p.scan {
+                    Some(done) => format!("{} {icon} {done:.1}%", p.name),
+                    None => format!("{} {icon}", p.name),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let online = pools.iter().all(zfs::Pool::is_online);
+        let block = Block::new("zfs", zfs::ICON, text);
+        status.push(if online {
+            block.color(GREEN)
+        } else {
+            block.color(RED)
+        });
+    }
+
+    // SMART health, red when a drive fails its self-assessment
+    if config.smart.enabled
+        && shown("smart")
+        && let Some(drives) = &*polled.smart.lock().unwrap()
+        && !drives.is_empty()
+    {
+        let text = drives
+            .iter()
+            .map(|d| {
+                let health = match d.passed {
+                    Some(true) => "ok",
+                    Some(false) => "FAILED",
+                    None => "n/a",
+                };
+                match d.temperature {
+                    Some(temp) => {
+                        let temp = temperature::convert(temp, temp_unit);
+                        format!("{} {health} {temp:.0}{temp_suffix}", d.name)
+                    }
+                    None => format!("{} {health}", d.name),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut block = Block::new("smart", smart::ICON, text);
+        if drives.iter().any(|d| d.passed == Some(false)) {
+            block = block.color(RED);
+        }
+        status.push(block);
+    }
 
     // Load Average
-    // status.push(json!({
-    //     "full_text": format!("󰓅 {:.1}", read_load_avg("/proc/loadavg").unwrap().0),
-    //     "name": "load"
-    // }));
+    if config.load.enabled
+        && shown("load")
+        && let Ok((load1, _, _)) = read_load_avg("/proc/loadavg")
+    {
+        let text = config.load.format.apply(load1, "", numbers);
+        let block = Block::new("load", "󰓅", text)
+            .value(load1)
+            .urgent_at(load1, config.load.urgent);
+        status.push(block);
+    }
+
+    // CPU Usage, which the poller only has after its first interval.
+    let cpu_usage = polled.cpu.lock().unwrap().clone();
+    if config.cpu.enabled
+        && shown("cpu")
+        && cpu_usage.is_none()
+        && config.output.warmup == Warmup::Placeholder
+    {
+        status.push(Block::new("cpu", cpu::ICON, "--"));
+    }
+    if config.cpu.enabled
+        && shown("cpu")
+        && let Some(usage) = &cpu_usage
+    {
+        let text = config.cpu.format.apply(usage.global, "%", numbers);
+        let tooltip = usage
+            .cores
+            .iter()
+            .enumerate()
+            .map(|(i, core)| format!("cpu{i} {core:.0}%"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let block = Block::new("cpu", cpu::ICON, text)
+            .percentage(usage.global)
+            .tooltip(tooltip)
+            .urgent_at(usage.global, config.cpu.urgent);
+        status.push(block);
+    }
+
+    // Swap activity, red while pages are moving
+    if config.swap.enabled
+        && shown("swap")
+        && let Some(rate) = *polled.swap.lock().unwrap()
+    {
+        let text = format!(
+            "↓{} ↑{}",
+            numbers.count(rate.pages_in.round() as u64),
+            numbers.count(rate.pages_out.round() as u64)
+        );
+        let total = rate.pages_in + rate.pages_out;
+        let mut block = Block::new("swap", swap::ICON, text).value(total);
+        if total > config.swap.active_rate {
+            block = block.color(RED);
+        }
+        status.push(block);
+    }
 
-    // CPU Usage
-    //status.push(json!({
-    //    "full_text": format!(" {:4.1}", sys.global_cpu_usage()),
-    //    "name": "cpu"
-    //}));
     // Memory Usage
-    //status.push(json!({
-    //     "full_text": format!(" {:4.1}", (sys.used_memory() as f32 / sys.total_memory() as f32) * 100.0),
-    //     "name": "memory"
-    // }));
+    if config.memory.enabled && shown("memory") {
+        let used = sys.used_memory() as f32 / sys.total_memory() as f32;
+        let cfg = &config.memory;
+        let text = cfg.format.apply(used * 100.0, "%", numbers);
+        let mut block = Block::new("memory", memory::ICON, text.as_str())
+            .percentage(used * 100.0)
+            .urgent_at(used * 100.0, cfg.urgent);
+        if cfg.bar
+            && let Some(info) = memory::get_meminfo()
+        {
+            let share = |kb: u64| kb as f32 / info.total as f32;
+            let (bar, bar_markup) = render_bar(
+                &[
+                    (share(info.used()), '█', &cfg.used_color),
+                    (share(info.cache), '▒', &cfg.cache_color),
+                    (share(info.free), '░', &cfg.free_color),
+                ],
+                cfg.bar_width,
+            );
+            block.text = format!("{text} {bar}");
+            block = block.markup(format!("{text} {bar_markup}"));
+        }
+        status.push(block);
+    }
+
+    // Connectivity
+    if config.connectivity.enabled
+        && shown("connectivity")
+        && let Some(online) = *polled.online.lock().unwrap()
+    {
+        let color = if online { GREEN } else { RED };
+        let tooltip = match route::default_route() {
+            Some(r) => format!("default route via {} on {}", r.gateway, r.iface),
+            None => "no default route".to_string(),
+        };
+        let block = Block::new("connectivity", connectivity::ICON, "")
+            .color(color)
+            .tooltip(tooltip);
+        status.push(block);
+    }
+
+    // Default route, red when there is none
+    if config.route.enabled && shown("route") {
+        let block = match route::default_route() {
+            Some(r) => {
+                let text = if config.route.show_metric {
+                    format!("via {} ({})", r.iface, r.metric)
+                } else {
+                    format!("via {}", r.iface)
+                };
+                Block::new("route", route::ICON, text)
+                    .instance(&r.iface)
+                    .tooltip(format!("gateway {}, metric {}", r.gateway, r.metric))
+            }
+            None => Block::new("route", route::ICON, "no route").color(RED),
+        };
+        status.push(block);
+    }
+
+    // Latency, green when quick and red when slow or unreachable
+    if config.latency.enabled
+        && shown("latency")
+        && let Some(latency) = *polled.latency.lock().unwrap()
+    {
+        let cfg = &config.latency;
+        let block = match latency {
+            Some(ms) => {
+                let text = cfg.format.apply(ms, "ms", numbers);
+                let color = threshold_color(ms, cfg.warning, cfg.critical).unwrap_or(GREEN);
+                Block::new("latency", connectivity::LATENCY_ICON, text)
+                    .value(ms)
+                    .color(color)
+            }
+            None => Block::new("latency", connectivity::LATENCY_ICON, "timeout").color(RED),
+        };
+        status.push(block.tooltip(format!("TCP connect to {}", cfg.target)));
+    }
+
+    // Containers
+    if config.docker.enabled
+        && shown("docker")
+        && let Some(count) = *polled.containers.lock().unwrap()
+    {
+        let color = if count == 0 {
+            &config.docker.color_zero
+        } else {
+            &config.docker.color_nonzero
+        };
+        let mut block =
+            Block::new("docker", docker::ICON, numbers.count(count.into())).value(count as f32);
+        block.color = color.clone();
+        status.push(block);
+    }
+
+    // Git repositories
+    if config.git.enabled
+        && shown("git")
+        && let Some(repos) = &*polled.git.lock().unwrap()
+    {
+        for repo in repos {
+            let mut block =
+                Block::new("git", git::ICON, git::format_git(repo)).instance(&repo.path);
+            if repo.dirty {
+                block = block.color(YELLOW);
+            }
+            status.push(block);
+        }
+    }
+
+    // Public IP
+    if config.public_ip.enabled
+        && shown("public_ip")
+        && let Some(addrs) = &*polled.public_ip.lock().unwrap()
+    {
+        let text = addrs
+            .iter()
+            .map(IpAddr::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut block = Block::new("public_ip", public_ip::ICON, text);
+        if public_ip::is_leaking(addrs, &config.public_ip) {
+            block = block.color(RED);
+        }
+        status.push(block);
+    }
+
+    // VPN leak alert, also raised when the public IP is outside the VPN ranges
+    if config.vpn.enabled && shown("vpn") {
+        let iface = &config.vpn.interface;
+        let route_leak =
+            config.vpn.check_route && route::default_route().is_none_or(|r| &r.iface != iface);
+        let ip_leak = config.public_ip.enabled
+            && polled
+                .public_ip
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|addrs| public_ip::is_leaking(addrs, &config.public_ip));
+        let alert = if !check_interface_enable(iface) {
+            Some("VPN DOWN")
+        } else if route_leak || ip_leak {
+            Some("VPN LEAK")
+        } else {
+            None
+        };
+        if let Some(text) = alert {
+            let mut block = Block::new("vpn", "󰻌", text).color(RED);
+            block.urgent = true;
+            status.push(block);
+        }
+    }
+
+    // WireGuard tunnels, green while the handshake is recent
+    if config.wireguard.enabled
+        && shown("wireguard")
+        && let Some(tunnels) = &*polled.wireguard.lock().unwrap()
+    {
+        let now = Local::now().timestamp();
+        for tunnel in tunnels {
+            let (text, fresh) = match tunnel.handshake {
+                Some(time) => (
+                    format!("{} {}", tunnel.iface, wireguard::age(now - time)),
+                    now - time <= config.wireguard.stale as i64,
+                ),
+                None => (tunnel.iface.clone(), false),
+            };
+            let block = Block::new("wireguard", wireguard::ICON, text)
+                .instance(&tunnel.iface)
+                .color(if fresh { GREEN } else { RED });
+            status.push(block);
+        }
+    }
+
+    // Caffeine
+    if config.caffeine.enabled && shown("caffeine") {
+        let mut block = Block::new("caffeine", caffeine::ICON, "");
+        if caffeine::is_active(&clicks.caffeine) {
+            block = block.color(config.caffeine.color.clone());
+        }
+        status.push(block);
+    }
+
+    // Sessions
+    if config.sessions.enabled
+        && shown("sessions")
+        && let Some(s) = *polled.sessions.lock().unwrap()
+        && !(config.sessions.hide_single && s.total <= 1 && s.remote == 0)
+    {
+        let block = if s.remote > 0 {
+            Block::new(
+                "sessions",
+                sessions::ICON,
+                format!("{} ({} ssh)", s.total, s.remote),
+            )
+            .color(YELLOW)
+        } else {
+            Block::new("sessions", sessions::ICON, s.total.to_string())
+        };
+        status.push(block.value(s.total as f32));
+    }
+
+    // Tasks
+    if config.tasks.enabled
+        && shown("tasks")
+        && let Some(count) = *polled.tasks.lock().unwrap()
+        && count > 0
+    {
+        status.push(
+            Block::new("tasks", tasks::ICON, numbers.count(count.into())).value(count as f32),
+        );
+    }
+
+    // Mail
+    if config.mail.enabled
+        && shown("mail")
+        && let Some(count) = *polled.mail.lock().unwrap()
+        && count > 0
+    {
+        status
+            .push(Block::new("mail", mail::ICON, numbers.count(count.into())).value(count as f32));
+    }
+
+    // Recent journal entries, e.g. errors
+    if config.journal.enabled
+        && shown("journal")
+        && let Some(count) = *polled.journal.lock().unwrap()
+        && count > 0
+    {
+        let block = Block::new("journal", journal::ICON, numbers.count(count.into()));
+        status.push(block.color(RED).value(count as f32));
+    }
+
+    // Next sunrise or sunset
+    if config.sun.enabled && shown("sun") {
+        let cfg = &config.sun;
+        let show = |icon, at: chrono::DateTime<chrono::Utc>| {
+            let text = if cfg.countdown {
+                format!("in {}", calendar::countdown(at - now.to_utc()))
+            } else {
+                at.with_timezone(&Local).format("%H:%M").to_string()
+            };
+            (icon, text)
+        };
+        let (icon, text) =
+            match sun::next(now.to_utc(), now.date_naive(), cfg.latitude, cfg.longitude) {
+                sun::Next::Rise(at) => show(sun::SUNRISE_ICON, at),
+                sun::Next::Set(at) => show(sun::SUNSET_ICON, at),
+                sun::Next::PolarDay => (sun::POLAR_DAY_ICON, "polar day".to_string()),
+                sun::Next::PolarNight => (sun::POLAR_NIGHT_ICON, "polar night".to_string()),
+            };
+        status.push(Block::new("sun", icon, text));
+    }
+
+    // Next calendar event, yellow once it is close
+    if config.calendar.enabled
+        && shown("calendar")
+        && let Some(events) = &*polled.calendar.lock().unwrap()
+        && let Some(event) = calendar::next(events, now.naive_local())
+    {
+        let left = event.start - now.naive_local();
+        let text = format!("in {}: {}", calendar::countdown(left), event.title);
+        let mut block = Block::new("calendar", calendar::ICON, text);
+        if left.num_seconds() < config.calendar.warning as i64 * 60 {
+            block = block.color(YELLOW);
+        }
+        status.push(block);
+    }
+
+    // Screen color temperature
+    if config.gammastep.enabled
+        && shown("gammastep")
+        && let Some(state) = *polled.gammastep.lock().unwrap()
+    {
+        let icon = gammastep::icon(state.period);
+        if gammastep::is_off() {
+            status.push(Block::new("gammastep", icon, "off"));
+        } else {
+            let text = format!("{}K", state.temperature);
+            status.push(Block::new("gammastep", icon, text).value(state.temperature as f32));
+        }
+    }
+
+    // Clipboard contents
+    if config.clipboard.enabled
+        && shown("clipboard")
+        && let Some(size) = *polled.clipboard.lock().unwrap()
+        && (size > 0 || !config.clipboard.hide_empty)
+    {
+        let text = match size {
+            0 => "empty".to_string(),
+            _ if config.clipboard.show_size => readable_bytes(size as f32, numbers),
+            _ => String::new(),
+        };
+        status.push(Block::new("clipboard", clipboard::ICON, text).value(size as f32));
+    }
+
+    // Screen recording
+    if config.recording.enabled
+        && shown("recording")
+        && *polled.recording.lock().unwrap() == Some(true)
+    {
+        status.push(
+            Block::new("recording", recording::ICON, "REC")
+                .color(anim.alert_color(&config.recording.blink)),
+        );
+    }
+
+    // Focused window
+    if let Some(blocks) = reuse("window") {
+        status.extend(blocks);
+    } else if config.window.enabled
+        && shown("window")
+        && let Some(title) = &*polled.window.lock().unwrap()
+    {
+        let text = window::truncate(title, config.window.max_length);
+        status.push(Block::new("window", window::ICON, text).tooltip(title.as_str()));
+    }
+
+    // Container layout
+    if let Some(blocks) = reuse("layout") {
+        status.extend(blocks);
+    } else if config.layout.enabled
+        && shown("layout")
+        && let Some(mode) = &*polled.layout.lock().unwrap()
+    {
+        status.push(Block::new("layout", layout::icon(mode), mode.as_str()));
+    }
+
+    // Script blocks
+    for (name, script) in &config.scripts {
+        let max_age = script.max_age.map(Duration::from_secs);
+        if let Some(text) = polled
+            .scripts
+            .get(name, max_age, script.placeholder.as_deref())
+            && shown(name)
+        {
+            status.push(Block::new(name.as_str(), script.icon.as_str(), text));
+        }
+    }
+
+    // Transient blocks pushed over IPC
+    for (name, text) in ipc.live_transients() {
+        if !shown(&name) {
+            continue;
+        }
+        status.push(Block::new(name, "", text));
+    }
+
+    // Focus timer
+    if shown("timer")
+        && let Some(t) = ipc.timer.lock().unwrap().as_mut()
+    {
+        let remaining = t
+            .deadline
+            .saturating_duration_since(std::time::Instant::now());
+        let mut block = Block::new("timer", timer::ICON, timer::format_timer(remaining));
+        if remaining.is_zero() {
+            block = block.color(anim.alert_color(&config.timer.blink));
+            if !t.fired {
+                t.fired = true;
+                if let Some(command) = &config.timer.on_expire {
+                    timer::run_on_expire(command);
+                }
+            }
+        }
+        status.push(block);
+    }
 
     // Volume
-    status.push(json!({
-        "full_text": format_volume(volume),
-        "name": "volume",
-    }));
+    if shown("volume") {
+        let ramp = config.volume.icons.steps_or(VOLUME_RAMP);
+        let (icon, text) = render_level(volume as f32, &ramp, &config.volume.format, numbers);
+        let block = Block::new("volume", icon, text)
+            .instance(PULSE_SINK)
+            .percentage(volume as f32);
+        status.push(block);
+    }
+
+    // PipeWire sample rate and quantum
+    if config.pipewire.enabled
+        && shown("pipewire")
+        && let Some(graph) = &*polled.pipewire.lock().unwrap()
+    {
+        status.push(Block::new("pipewire", pipewire::ICON, graph.describe()));
+    }
 
     // Brightness
-    if let Ok(brightness) = get_brightness() {
-        status.push(json!({
-            "full_text": format!("  {}", brightness),
-            "name": "brightness",
-        }));
+    if shown("brightness")
+        && let Some(level) = sources.brightness
+    {
+        let ramp = config.brightness.icons.steps_or(brightness::RAMP);
+        let (icon, text) = render_level(level, &ramp, &config.brightness.format, numbers);
+        let block = Block::new("brightness", icon, text)
+            .instance(brightness::device_name().unwrap_or_default())
+            .percentage(level);
+        status.push(block);
+    }
+
+    // Keyboard backlight, left out on machines without one
+    if shown("kbd_backlight")
+        && let Some((device, level)) = sources.kbd_backlight.clone()
+    {
+        let cfg = &config.kbd_backlight;
+        let ramp = cfg.icons.steps_or(&[(0.0, kbd_backlight::ICON)]);
+        let (icon, text) = render_level(level, &ramp, &cfg.format, numbers);
+        let block = Block::new("kbd_backlight", icon, text)
+            .instance(device)
+            .percentage(level);
+        status.push(block);
+    }
+
+    // Battery, with an arrow for the direction of the last change
+    if config.battery.enabled
+        && shown("battery")
+        && let Some(bat) = &sources.battery
+    {
+        let cfg = &config.battery;
+        let ramp = cfg.icons.steps_or(battery::RAMP);
+        let (mut icon, mut text) = render_level(bat.percent, &ramp, &cfg.format, numbers);
+        if bat.charging {
+            icon = battery::CHARGING_ICON;
+        }
+        if cfg.trend {
+            let trend = trends.update("battery", bat.percent, 0.0, true);
+            if trend != Trend::Steady {
+                text.push_str(trend.symbol());
+            }
+        }
+        let mut block = Block::new("battery", icon, text)
+            .instance(&cfg.device)
+            .percentage(bat.percent);
+        if bat.percent <= cfg.critical {
+            block = block.color(RED);
+        } else if bat.percent <= cfg.warning {
+            block = block.color(YELLOW);
+        }
+        status.push(block);
+    }
+
+    // Power profile or CPU governor
+    if config.power_profile.enabled
+        && shown("power_profile")
+        && let Some(profile) = &*polled.power_profile.lock().unwrap()
+    {
+        let icon = power_profile::icon(profile);
+        status.push(Block::new("power_profile", icon, profile.as_str()));
     }
 
     // Fan Speed
@@ -335,94 +1522,726 @@ fn print_status(sys: &mut System, volume: u32, tracker: &mut NetTracker) {
     //}
 
     // Time & Date
-    status.push(json!({
-        "full_text": format!("󰥔  {} ", time),
-        "name": "clock",
-    }));
+    if shown("clock") {
+        let clicked = *clicks.clock_clicked.lock().unwrap();
+        let text = clock::format_clock(&now, &config.clock, anim.tick, clicked);
+        status.push(Block::new("clock", clock::ICON, text).instance("local"));
+    }
     // status.push(json!({
     //      "full_text": format!("  {}", day),
     //      "name": "date"
     // }));
 
-    // Output status as JSON
-    println!("{},", serde_json::to_string(&status).unwrap());
+    held.clear();
+    for name in &config.output.event_only {
+        let blocks = status.iter().filter(|b| &b.name == name).cloned().collect();
+        held.insert(name.clone(), blocks);
+    }
+
+    // The battery draws its own arrow when `battery.trend` is on.
+    let own_trend = |name: &str| name == "battery" && config.battery.trend;
+    for block in &mut status {
+        if let Some(deadband) = config.trends.get(&block.name)
+            && !own_trend(&block.name)
+            && let Some(value) = block.value
+        {
+            let key = match &block.instance {
+                Some(instance) => format!("{}:{instance}", block.name),
+                None => block.name.clone(),
+            };
+            let trend = trends.update(&key, value, *deadband, false);
+            block.text.push_str(trend.symbol());
+            if let Some(markup) = &mut block.markup {
+                markup.push_str(trend.symbol());
+            }
+        }
+    }
+
+    icons::apply(&mut status, config.style.icon_set);
+    let all = status.clone();
+    status.retain(|b| {
+        let hide = config.hide_when.get(&b.name);
+        !hide.is_some_and(|cond| b.value.is_some_and(|v| cond.matches(v)))
+    });
+    (block::merge(status, &config.merges, &config.style), all)
 }
 
 fn get_country_code() -> Result<String, Box<dyn Error>> {
-    let output = Command::new("nordvpn").arg("status").output()?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = command::run_with_timeout(Command::new("nordvpn").arg("status"), COMMAND_TIMEOUT)?;
     for line in stdout.lines() {
-        if line.starts_with("Hostname:") {
-            if let Some(hostname) = line.split_whitespace().nth(1) {
-                return Ok(hostname
-                    .chars()
-                    .map(|x| x.to_ascii_uppercase())
-                    .take(2)
-                    .collect());
-            }
+        if line.starts_with("Hostname:")
+            && let Some(hostname) = line.split_whitespace().nth(1)
+        {
+            return Ok(hostname
+                .chars()
+                .map(|x| x.to_ascii_uppercase())
+                .take(2)
+                .collect());
         }
     }
     Err("Hostname Line not found".into())
 }
 
-fn main() {
-    println!(r#"{{ "version": 1 }}"#);
-    println!("[");
+/// Command line options.
+#[derive(Default)]
+struct Args {
+    /// Validate the config file and exit.
+    check_config: bool,
+    /// Report which data sources are usable and exit.
+    doctor: bool,
+    /// Overrides the configured output format.
+    format: Option<OutputFormat>,
+    /// Overrides the block printed in `waybar` output.
+    block: Option<String>,
+    /// Print a single status line and exit.
+    once: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--check-config" => args.check_config = true,
+            "--doctor" => args.doctor = true,
+            "--once" => args.once = true,
+            "--block" => args.block = Some(argv.next().ok_or("--block needs a value")?),
+            "--format" => {
+                let name = argv.next().ok_or("--format needs a value")?;
+                let format = OutputFormat::parse(&name)
+                    .ok_or_else(|| format!("unknown output format: {name}"))?;
+                args.format = Some(format);
+            }
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+    Ok(args)
+}
+
+/// Stop the idle inhibitor when the bar is told to quit, e.g. by i3 on a
+/// reload, so it does not outlive the bar. Has to run before any other thread
+/// is started, so they all inherit the blocked signals and leave them to
+/// this one.
+fn spawn_signal_handler(clicks: Arc<ClickState>) {
+    let mut signals = SigSet::empty();
+    for signal in [Signal::SIGTERM, Signal::SIGINT, Signal::SIGHUP] {
+        signals.add(signal);
+    }
+    if let Err(e) = signals.thread_block() {
+        eprintln!("rocketbar: cannot handle signals: {e}");
+        return;
+    }
+    thread::spawn(move || {
+        if let Ok(signal) = signals.wait() {
+            caffeine::stop(&clicks.caffeine);
+            std::process::exit(128 + signal as i32);
+        }
+    });
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("rocketbar: {e}");
+            eprintln!(
+                "usage: rocketbar [--check-config] [--doctor] [--once] \
+                 [--format i3bar|plain|ndjson|lemonbar|tmux|waybar] [--block NAME]"
+            );
+            return ExitCode::from(2);
+        }
+    };
+    if args.check_config {
+        return if config::check() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+
+    if args.doctor {
+        return if doctor::run(&config::load()) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+
+    let config = Arc::new(RwLock::new(config::load()));
+    if !any_block_enabled(&config.read().unwrap()) {
+        eprintln!(
+            "rocketbar: no blocks are enabled; check the `enabled` settings and groups in the config"
+        );
+        if args.once {
+            return ExitCode::FAILURE;
+        }
+    }
+    let mut output = {
+        let cfg = &config.read().unwrap().output;
+        Output {
+            format: args.format.unwrap_or(cfg.format),
+            block: args.block.or_else(|| cfg.block.clone()),
+            last_line: None,
+        }
+    };
+    output::print_header(&output);
 
     let volume = Arc::new(Mutex::new(get_volume().unwrap_or(0)));
     let pair = Arc::new((Mutex::new(false), Condvar::new()));
+    let polled = Polled::default();
+    let ipc_state = Arc::new(IpcState::default());
+    let mut anim = AnimationState::default();
     let mut sys = System::new_all();
 
+    // A one-shot run must not take over the socket or input of a running bar.
+    let click_state = Arc::new(ClickState::default());
+    if !args.once {
+        caffeine::reap_leftover();
+        spawn_signal_handler(Arc::clone(&click_state));
+        config::spawn_watcher(Arc::clone(&config), Arc::clone(&pair));
+    }
+    if output.format == OutputFormat::I3bar && !args.once {
+        click::spawn_reader(
+            Arc::clone(&config),
+            Arc::clone(&click_state),
+            Arc::clone(&pair),
+        );
+    }
+
+    let ipc_config = config.read().unwrap().ipc.clone();
+    if ipc_config.enabled && !args.once {
+        let path = ipc::socket_path(&ipc_config);
+        if let Err(e) = ipc::spawn_listener(
+            path,
+            Arc::clone(&config),
+            Arc::clone(&ipc_state),
+            Arc::clone(&pair),
+        ) {
+            eprintln!("rocketbar: ipc: {e}");
+        }
+    }
+
+    let mut sampler = None;
+    spawn_poller(
+        Arc::clone(&polled.cpu),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.cpu.interval,
+        move |c| {
+            if c.cpu.enabled {
+                let sampler = sampler.get_or_insert_with(cpu::Sampler::new);
+                Some(sampler.sample(c.cpu.samples))
+            } else {
+                None
+            }
+        },
+    );
+
+    let mut vmstat = swap::VmstatTracker::default();
+    spawn_poller(
+        Arc::clone(&polled.swap),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.swap.interval,
+        move |c| {
+            if c.swap.enabled && swap::enabled() {
+                vmstat.update()
+            } else {
+                // Start over once swap is back rather than average the gap.
+                vmstat = swap::VmstatTracker::default();
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.temperature),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.temperature.interval,
+        |c| {
+            if c.temperature.enabled {
+                let components = Components::new_with_refreshed_list();
+                temperature::get_temperature(&components, &c.temperature)
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.hottest),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.hottest.interval,
+        |c| {
+            if c.hottest.enabled {
+                let components = Components::new_with_refreshed_list();
+                temperature::get_hottest(&components, &c.hottest)
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.wireguard),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.wireguard.interval,
+        |c| {
+            if c.wireguard.enabled {
+                wireguard::get_tunnels()
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.vpn_country),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.net.country_interval,
+        |c| {
+            let net = &c.net;
+            if net.enabled && net.vpn_country && check_interface_enable(&net.vpn_interface) {
+                get_country_code().ok()
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.containers),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.docker.interval,
+        |c| {
+            if c.docker.enabled {
+                docker::get_container_count(&c.docker)
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.tasks),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.tasks.interval,
+        |c| {
+            if c.tasks.enabled {
+                tasks::get_task_count(&c.tasks)
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.calendar),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.calendar.interval,
+        |c| {
+            if c.calendar.enabled {
+                calendar::get_events(&c.calendar.command, Local::now().date_naive())
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.mail),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.mail.interval,
+        |c| {
+            if c.mail.enabled {
+                mail::get_unread_count(&c.mail)
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.journal),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.journal.interval,
+        |c| {
+            if c.journal.enabled {
+                journal::get_count(&c.journal)
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.git),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.git.interval,
+        |c| {
+            if c.git.enabled {
+                Some(
+                    c.git
+                        .repos
+                        .iter()
+                        .filter_map(|r| git::get_git_status(r))
+                        .collect(),
+                )
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.online),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.connectivity.interval,
+        |c| {
+            if c.connectivity.enabled {
+                Some(connectivity::is_online(&c.connectivity))
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.latency),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.latency.interval,
+        |c| {
+            if c.latency.enabled {
+                Some(connectivity::latency(&c.latency))
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.sessions),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.sessions.interval,
+        |c| {
+            if c.sessions.enabled {
+                sessions::get_sessions()
+            } else {
+                None
+            }
+        },
+    );
+
+    // Lookups fail whenever the network does; keep showing the last address.
+    let mut last_public_ip = None;
+    spawn_poller(
+        Arc::clone(&polled.public_ip),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.public_ip.interval,
+        move |c| {
+            if !c.public_ip.enabled {
+                return None;
+            }
+            if let Some(addrs) = public_ip::get_public_ip(&c.public_ip) {
+                last_public_ip = Some(addrs);
+            }
+            last_public_ip.clone()
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.gammastep),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.gammastep.interval,
+        |c| {
+            if c.gammastep.enabled {
+                gammastep::get_state()
+            } else {
+                None
+            }
+        },
+    );
+
+    clipboard::spawn_watcher(
+        Arc::clone(&polled.clipboard),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+    );
+
+    window::spawn_subscriber(
+        Arc::clone(&polled.window),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+    );
+
+    layout::spawn_subscriber(
+        Arc::clone(&polled.layout),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+    );
+
+    binding_mode::spawn_subscriber(
+        Arc::clone(&polled.binding_mode),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+    );
+
+    script::spawn_scripts(
+        Arc::clone(&config),
+        Arc::clone(&polled.scripts),
+        Arc::clone(&pair),
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.zfs),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.zfs.interval,
+        |c| {
+            if c.zfs.enabled {
+                zfs::get_pools()
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.pipewire),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.pipewire.interval,
+        |c| {
+            if c.pipewire.enabled {
+                pipewire::get_graph()
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.power_profile),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.power_profile.interval,
+        |c| {
+            if c.power_profile.enabled {
+                power_profile::get_profile()
+            } else {
+                None
+            }
+        },
+    );
+
+    spawn_poller(
+        Arc::clone(&polled.smart),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.smart.interval,
+        |c| {
+            if c.smart.enabled {
+                Some(smart::get_drives(&c.smart.devices))
+            } else {
+                None
+            }
+        },
+    );
+
+    let mut procs = System::new();
+    spawn_poller(
+        Arc::clone(&polled.recording),
+        Arc::clone(&config),
+        Arc::clone(&pair),
+        |c| c.recording.interval,
+        move |c| {
+            if c.recording.enabled {
+                Some(recording::is_recording(&mut procs, &c.recording))
+            } else {
+                None
+            }
+        },
+    );
+
     // Volume change listener thread
-    {
+    if !args.once {
         let volume_clone = Arc::clone(&volume);
         let pair_clone = Arc::clone(&pair);
 
         thread::spawn(move || {
-            let mut child = Command::new("pactl")
+            // The C locale keeps the event lines in English.
+            let mut child = match Command::new("pactl")
                 .arg("subscribe")
+                .env("LC_ALL", "C")
                 .stdout(Stdio::piped())
                 .spawn()
-                .expect("Failed to run pactl subscribe");
+            {
+                Ok(child) => child,
+                Err(e) => {
+                    eprintln!("rocketbar: pactl subscribe: {e}; volume will not update");
+                    return;
+                }
+            };
 
             let stdout = child.stdout.take().expect("No stdout from pactl");
             let reader = BufReader::new(stdout);
 
-            for line in reader.lines() {
-                if let Ok(event) = line {
-                    if event.contains("Event 'change' on sink") {
-                        if let Some(new_vol) = get_volume() {
-                            let mut vol_lock = volume_clone.lock().unwrap();
-                            if *vol_lock != new_vol {
-                                *vol_lock = new_vol;
-                                let (lock, cvar) = &*pair_clone;
-                                let mut notified = lock.lock().unwrap();
-                                *notified = true;
-                                cvar.notify_one();
-                            }
-                        }
+            for event in reader.lines().map_while(Result::ok) {
+                if affects_volume(&event)
+                    && let Some(new_vol) = get_volume()
+                {
+                    let mut vol_lock = volume_clone.lock().unwrap();
+                    if *vol_lock != new_vol {
+                        *vol_lock = new_vol;
+                        wake(&pair_clone);
                     }
                 }
             }
+            if let Ok(status) = child.wait()
+                && !status.success()
+            {
+                eprintln!("rocketbar: pactl subscribe: {status}; volume will not update");
+            }
         });
     }
 
-    let mut net_state = NetTracker {
-        last_up: 0,
-        last_down: 0,
-        last_time: std::time::Instant::now(),
-    };
+    let mut net_trackers = HashMap::new();
+    let mut trends = Trends::default();
+    let mut held = HashMap::new();
+    let mut metrics = MetricsWriter::default();
+    let readers = SourceReaders::default();
 
-    // First output
-    print_status(&mut sys, *volume.lock().unwrap(), &mut net_state);
-
-    // Subsequent updates
     let (lock, cvar) = &*pair;
+    if args.once {
+        // Let the pollers' first results and a CPU usage sample come in,
+        // but stay quick since tmux runs this every few seconds.
+        build_status(
+            &mut sys,
+            &config.read().unwrap(),
+            *volume.lock().unwrap(),
+            &polled,
+            &ipc_state,
+            &click_state,
+            &mut anim,
+            &mut net_trackers,
+            &mut trends,
+            false,
+            &mut held,
+            &readers,
+        );
+        let deadline = Instant::now() + ONCE_SETTLE;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let notified = lock.lock().unwrap();
+            let _ = cvar.wait_timeout(notified, left).unwrap();
+        }
+        let config = config.read().unwrap();
+        let (status, _) = build_status(
+            &mut sys,
+            &config,
+            *volume.lock().unwrap(),
+            &polled,
+            &ipc_state,
+            &click_state,
+            &mut anim,
+            &mut net_trackers,
+            &mut trends,
+            false,
+            &mut held,
+            &readers,
+        );
+        output::print(&mut output, &status, &config);
+        return ExitCode::SUCCESS;
+    }
+
+    let output = Arc::new(Mutex::new(output));
+    let heartbeat = Arc::new(Mutex::new(Heartbeat::new()));
+    watchdog::spawn(
+        Arc::clone(&output),
+        Arc::clone(&heartbeat),
+        Arc::clone(&config),
+    );
+
+    // Print right away, then every interval or whenever a thread wakes us
+    let mut timer_tick = false;
     loop {
-        let notified = lock.lock().unwrap();
-        let _ = cvar.wait_timeout(notified, Duration::from_secs(1)).unwrap();
+        let interval = {
+            let config = config.read().unwrap();
+            power::update(&config);
+            let vol = *volume.lock().unwrap();
+            let (status, all) = build_status(
+                &mut sys,
+                &config,
+                vol,
+                &polled,
+                &ipc_state,
+                &click_state,
+                &mut anim,
+                &mut net_trackers,
+                &mut trends,
+                timer_tick,
+                &mut held,
+                &readers,
+            );
+            output::print(&mut output.lock().unwrap(), &status, &config);
+            metrics.record(&all, &config);
+            let clicked = *click_state.clock_clicked.lock().unwrap();
+            let interval = if config.output.align_to_minute
+                && !clock::showing_click_format(&config.clock, clicked)
+            {
+                clock::until_next_minute(&Local::now())
+            } else {
+                power::tick(&config)
+            };
+            heartbeat.lock().unwrap().beat(status, interval);
+            anim.schedule(interval);
+            interval
+        };
+
+        // A wake that came in while drawing is picked up right away.
+        let (mut notified, _) = cvar
+            .wait_timeout_while(lock.lock().unwrap(), interval, |notified| !*notified)
+            .unwrap();
+        timer_tick = !*notified;
+        *notified = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compact_rate;
+    use crate::config::{NumberStyle, RateUnit, UnitBase};
+
+    fn rate(bytes: f32, base: u32) -> String {
+        compact_rate(
+            bytes,
+            RateUnit::Bytes,
+            UnitBase(base),
+            &NumberStyle::default(),
+        )
+    }
 
-        let vol = *volume.lock().unwrap();
-        print_status(&mut sys, vol, &mut net_state);
+    #[test]
+    fn compact_rate_rounds_before_picking_the_prefix() {
+        assert_eq!(rate(9.97 * 1024.0, 1024), "10K");
+        assert_eq!(rate(9.94 * 1024.0, 1024), "9.9K");
+        assert_eq!(rate(1023.9, 1024), "1.0K");
+        assert_eq!(rate(999.6, 1000), "1.0K");
+        assert_eq!(rate(999.4, 1000), "999");
+        assert_eq!(rate(0.4, 1000), "0");
     }
 }
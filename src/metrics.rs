@@ -0,0 +1,243 @@
+//! Writing block values to files every tick: appended to a log to graph
+//! them later, or as a Prometheus textfile for node_exporter. Writing happens
+//! on a background thread so a slow disk never holds up the bar.
+
+use crate::block::Block;
+use crate::config::{Config, MetricsFormat, expand_home};
+use chrono::{Local, SecondsFormat};
+use serde_json::{Map, Value, json};
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+enum Job {
+    Append(Record),
+    /// Replace the file at `path` with `contents`.
+    Replace {
+        path: PathBuf,
+        contents: String,
+    },
+}
+
+/// A line for the file at `path`, with the header to start the file with
+/// if it is empty.
+struct Record {
+    path: PathBuf,
+    header: Option<String>,
+    line: String,
+}
+
+/// The sending side of the writer thread, started on first use.
+#[derive(Default)]
+pub struct MetricsWriter(Option<Sender<Job>>);
+
+impl MetricsWriter {
+    /// Queue this tick's values for the outputs that are enabled.
+    pub fn record(&mut self, blocks: &[Block], config: &Config) {
+        if let Some(record) = log_record(blocks, config) {
+            self.send(Job::Append(record));
+        }
+        if config.prometheus.enabled {
+            self.send(Job::Replace {
+                path: expand_home(&config.prometheus.path),
+                contents: prometheus(blocks),
+            });
+        }
+    }
+
+    fn send(&mut self, job: Job) {
+        let sender = self.0.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || write_jobs(receiver));
+            sender
+        });
+        let _ = sender.send(job);
+    }
+}
+
+/// This tick's line for the metrics log, if it is enabled.
+fn log_record(blocks: &[Block], config: &Config) -> Option<Record> {
+    let cfg = &config.metrics_log;
+    if !cfg.enabled || cfg.blocks.is_empty() {
+        return None;
+    }
+    let values: Vec<Option<f32>> = cfg.blocks.iter().map(|key| value(blocks, key)).collect();
+    let time = Local::now().to_rfc3339_opts(SecondsFormat::Millis, false);
+    let (header, line) = match cfg.format {
+        MetricsFormat::Csv => {
+            let cells = values
+                .iter()
+                .map(|v| v.map(|v| v.to_string()).unwrap_or_default());
+            let line = std::iter::once(time).chain(cells).collect::<Vec<_>>();
+            let header = std::iter::once("time")
+                .chain(cfg.blocks.iter().map(String::as_str))
+                .collect::<Vec<_>>();
+            (Some(header.join(",")), line.join(","))
+        }
+        MetricsFormat::Ndjson => {
+            let mut object = Map::new();
+            object.insert("time".to_string(), json!(time));
+            for (key, value) in cfg.blocks.iter().zip(&values) {
+                // Going through the shortest text form keeps an f32
+                // like 5.26 from turning into 5.2600002 as an f64.
+                let value = value.and_then(|v| v.to_string().parse::<f64>().ok());
+                object.insert(key.clone(), json!(value));
+            }
+            (None, Value::Object(object).to_string())
+        }
+    };
+    Some(Record {
+        path: expand_home(&cfg.path),
+        header,
+        line,
+    })
+}
+
+/// Every block with a value as a gauge in the Prometheus text format, e.g.
+/// `rocketbar_cpu_percent 12.3`, or `rocketbar_net{instance="wlp2s0"} ...`
+/// for blocks with an instance. Percentages get a `_percent` suffix.
+fn prometheus(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    let mut done: Vec<&str> = Vec::new();
+    for block in blocks.iter().filter(|b| b.value.is_some()) {
+        if done.contains(&block.name.as_str()) {
+            continue;
+        }
+        done.push(&block.name);
+        let mut metric: String = block
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        metric.insert_str(0, "rocketbar_");
+        if block.percentage.is_some() {
+            metric.push_str("_percent");
+        }
+        // Every sample of a metric has to follow its one `# TYPE` line.
+        let _ = writeln!(out, "# TYPE {metric} gauge");
+        for sample in blocks.iter().filter(|b| b.name == block.name) {
+            let Some(value) = sample.value else {
+                continue;
+            };
+            match &sample.instance {
+                Some(instance) => {
+                    let instance = instance.replace('\\', "\\\\").replace('"', "\\\"");
+                    let _ = writeln!(out, "{metric}{{instance=\"{instance}\"}} {value}");
+                }
+                None => {
+                    let _ = writeln!(out, "{metric} {value}");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The value of the first block named `key`, or of the one with that
+/// instance when `key` is `name:instance`.
+fn value(blocks: &[Block], key: &str) -> Option<f32> {
+    let (name, instance) = match key.split_once(':') {
+        Some((name, instance)) => (name, Some(instance)),
+        None => (key, None),
+    };
+    blocks
+        .iter()
+        .filter(|b| b.name == name)
+        .find(|b| instance.is_none() || b.instance.as_deref() == instance)
+        .and_then(|b| b.value)
+}
+
+/// Carry out jobs as they come, flushing the log whenever the queue runs
+/// dry. The log is reopened when a config reload points it elsewhere or
+/// changes its columns.
+fn write_jobs(receiver: Receiver<Job>) {
+    let mut open: Option<(PathBuf, Option<String>, BufWriter<File>)> = None;
+    // The last path that failed, so an unwritable one is reported once.
+    let mut failed: Option<PathBuf> = None;
+    let mut failed_replace: Option<PathBuf> = None;
+    while let Ok(first) = receiver.recv() {
+        for job in std::iter::once(first).chain(receiver.try_iter()) {
+            let record = match job {
+                Job::Append(record) => record,
+                Job::Replace { path, contents } => {
+                    match replace(&path, &contents) {
+                        Ok(()) => failed_replace = None,
+                        Err(e) if failed_replace.as_ref() != Some(&path) => {
+                            eprintln!("rocketbar: prometheus {}: {e}", path.display());
+                            failed_replace = Some(path);
+                        }
+                        Err(_) => {}
+                    }
+                    continue;
+                }
+            };
+            if open
+                .as_ref()
+                .is_none_or(|(path, header, _)| *path != record.path || *header != record.header)
+            {
+                // Flush the old file before the new one might be rotated
+                // out from under it.
+                drop(open.take());
+                open = match open_log(&record) {
+                    Ok(file) => Some((record.path.clone(), record.header.clone(), file)),
+                    Err(e) => {
+                        if failed.as_ref() != Some(&record.path) {
+                            eprintln!("rocketbar: metrics log {}: {e}", record.path.display());
+                            failed = Some(record.path.clone());
+                        }
+                        None
+                    }
+                };
+            }
+            if let Some((_, _, file)) = &mut open {
+                let _ = writeln!(file, "{}", record.line);
+            }
+        }
+        if let Some((_, _, file)) = &mut open {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Write `contents` next to `path` and rename it into place, so a reader
+/// like node_exporter never sees a half-written file.
+fn replace(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+/// Open the log for appending. A CSV log whose header does not match the
+/// configured columns is first moved aside to `<path>.<time>`, so the new
+/// rows never land under the wrong column names.
+fn open_log(record: &Record) -> std::io::Result<BufWriter<File>> {
+    if let Some(dir) = record.path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if let Some(header) = &record.header
+        && let Ok(file) = File::open(&record.path)
+    {
+        let mut first = String::new();
+        BufReader::new(file).read_line(&mut first)?;
+        if !first.is_empty() && first.trim_end() != header {
+            let mut rotated = record.path.as_os_str().to_owned();
+            rotated.push(Local::now().format(".%Y%m%dT%H%M%S").to_string());
+            fs::rename(&record.path, rotated)?;
+        }
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&record.path)?;
+    let mut file = BufWriter::new(file);
+    if let Some(header) = &record.header
+        && file.get_ref().metadata()?.len() == 0
+    {
+        writeln!(file, "{header}")?;
+    }
+    Ok(file)
+}
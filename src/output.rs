@@ -0,0 +1,339 @@
+//! Serializers turning the blocks of one tick into a line of output.
+
+use crate::block::Block;
+use crate::config::{BUILTIN_BLOCKS, Config};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::sync::{LazyLock, Mutex};
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{(\w+)\}").unwrap());
+
+/// Unknown placeholders already warned about.
+static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// The i3bar/swaybar protocol: a header, then an endless JSON array.
+    I3bar,
+    /// The blocks' text joined by the configured separator.
+    Plain,
+    /// One JSON object per line, holding the blocks as i3bar would get them.
+    Ndjson,
+    /// lemonbar/polybar formatting tags, urgent blocks on a red background.
+    Lemonbar,
+    /// tmux `#[...]` style tags, for `#(rocketbar --once --format tmux)`.
+    Tmux,
+    /// One block per line as JSON for a Waybar `custom/*` module with
+    /// `return-type = "json"`.
+    Waybar,
+}
+
+impl OutputFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "i3bar" => Some(Self::I3bar),
+            "plain" => Some(Self::Plain),
+            "ndjson" => Some(Self::Ndjson),
+            "lemonbar" => Some(Self::Lemonbar),
+            "tmux" => Some(Self::Tmux),
+            "waybar" => Some(Self::Waybar),
+            _ => None,
+        }
+    }
+}
+
+/// Output settings fixed at startup, from the command line or the config.
+pub struct Output {
+    pub format: OutputFormat,
+    /// The block printed in `waybar` output.
+    pub block: Option<String>,
+    /// The line printed last, so an unchanged one can be skipped.
+    pub last_line: Option<String>,
+}
+
+/// Print whatever the format needs before the first line.
+pub fn print_header(output: &Output) {
+    if output.format == OutputFormat::I3bar {
+        println!(r#"{{ "version": 1, "click_events": true }}"#);
+        println!("[");
+    }
+}
+
+/// Print one tick's worth of blocks, unless it would repeat the last line
+/// and `output.dedup` is on.
+pub fn print(output: &mut Output, blocks: &[Block], config: &Config) {
+    let style = &config.style;
+    let reordered;
+    let blocks = match &config.output.reverse_after {
+        Some(name) => {
+            reordered = reverse_after(blocks, name);
+            &reordered
+        }
+        None => blocks,
+    };
+    let fitted;
+    let blocks = match config.output.max_width {
+        Some(max_width) if output.format != OutputFormat::Waybar => {
+            fitted = fit(blocks, max_width, output.format, config);
+            &fitted
+        }
+        _ => blocks,
+    };
+    let line = match output.format {
+        OutputFormat::I3bar => {
+            let blocks: Vec<_> =
+                if style.powerline.enabled && !style.powerline.backgrounds.is_empty() {
+                    powerline(blocks, config)
+                } else {
+                    blocks.iter().map(|b| b.to_json(style)).collect()
+                };
+            format!("{},", serde_json::to_string(&blocks).unwrap())
+        }
+        OutputFormat::Plain => match &config.output.template {
+            Some(template) => fill_template(template, blocks, config),
+            None => blocks
+                .iter()
+                .map(|b| b.full_text(style))
+                .collect::<Vec<_>>()
+                .join(&config.output.separator),
+        },
+        OutputFormat::Ndjson => {
+            let json: Vec<_> = blocks
+                .iter()
+                .map(|b| {
+                    let mut block = b.to_json(style);
+                    if let Some(tooltip) = &b.tooltip {
+                        block["tooltip"] = json!(tooltip);
+                    }
+                    block
+                })
+                .collect();
+            let mut line = json!({ "blocks": json });
+            if let Some(template) = &config.output.template {
+                line["text"] = json!(fill_template(template, blocks, config));
+            }
+            line.to_string()
+        }
+        OutputFormat::Lemonbar => blocks
+            .iter()
+            .map(|b| lemonbar_block(b, config))
+            .collect::<Vec<_>>()
+            .join(&config.output.separator),
+        OutputFormat::Tmux => blocks
+            .iter()
+            .map(|b| tmux_block(b, config))
+            .collect::<Vec<_>>()
+            .join(&config.output.separator),
+        OutputFormat::Waybar => {
+            let block = output
+                .block
+                .as_deref()
+                .and_then(|name| blocks.iter().find(|b| b.name == name));
+            waybar_block(block, config).to_string()
+        }
+    };
+    if config.output.dedup && output.last_line.as_ref() == Some(&line) {
+        return;
+    }
+    println!("{line}");
+    output.last_line = Some(line);
+}
+
+/// Reverse the blocks after the first one named `name`; all of them stay as
+/// they are when it is not shown.
+fn reverse_after(blocks: &[Block], name: &str) -> Vec<Block> {
+    let mut blocks = blocks.to_vec();
+    if let Some(split) = blocks.iter().position(|b| b.name == name) {
+        blocks[split + 1..].reverse();
+    }
+    blocks
+}
+
+/// Shorten and drop blocks until the line takes at most `max_width`
+/// characters. Blocks go in order of priority, leftmost first among equals:
+/// each is cut down to its icon if it has one, then dropped if the line is
+/// still too long.
+fn fit(blocks: &[Block], max_width: usize, format: OutputFormat, config: &Config) -> Vec<Block> {
+    let style = &config.style;
+    // The JSON formats leave spacing between blocks to the bar.
+    let gap = match format {
+        OutputFormat::Plain | OutputFormat::Lemonbar | OutputFormat::Tmux => {
+            config.output.separator.chars().count()
+        }
+        _ => 0,
+    };
+    let width = |blocks: &[Option<Block>]| {
+        let shown: Vec<_> = blocks.iter().flatten().collect();
+        let text: usize = shown
+            .iter()
+            .map(|b| b.full_text(style).chars().count())
+            .sum();
+        text + gap * shown.len().saturating_sub(1)
+    };
+    let priority = |block: &Block| {
+        style
+            .blocks
+            .get(&block.name)
+            .and_then(|o| o.priority)
+            .unwrap_or(0)
+    };
+    let mut order: Vec<usize> = (0..blocks.len()).collect();
+    order.sort_by_key(|&i| priority(&blocks[i]));
+
+    let mut fitted: Vec<Option<Block>> = blocks.iter().cloned().map(Some).collect();
+    for i in order {
+        if width(&fitted) <= max_width {
+            break;
+        }
+        if let Some(block) = &mut fitted[i]
+            && !block.icon.is_empty()
+            && !block.text.is_empty()
+        {
+            block.text.clear();
+            block.markup = None;
+            if width(&fitted) <= max_width {
+                break;
+            }
+        }
+        fitted[i] = None;
+    }
+    fitted.into_iter().flatten().collect()
+}
+
+/// Give the blocks backgrounds and put a separator glyph between them,
+/// colored with the background of the block after it over that of the block
+/// before it, so the blocks appear to flow into each other. A run of blocks
+/// from the same group shares the first one's background, without
+/// separators inside it.
+fn powerline(blocks: &[Block], config: &Config) -> Vec<Value> {
+    let style = &config.style;
+    let cfg = &style.powerline;
+    let group = |block: &Block| {
+        config
+            .groups
+            .iter()
+            .find(|(_, group)| group.blocks.contains(&block.name))
+            .map(|(name, _)| name)
+    };
+    let mut palette = cfg.backgrounds.iter().cycle();
+    let mut json = Vec::with_capacity(blocks.len() * 2);
+    // The block before and its background.
+    let mut previous: Option<(&Block, &str)> = None;
+    for block in blocks {
+        let run = previous
+            .filter(|(last, _)| group(last).is_some() && group(last) == group(block))
+            .map(|(_, background)| background);
+        let background = match run {
+            Some(background) => background,
+            None => style
+                .blocks
+                .get(&block.name)
+                .and_then(|b| b.background.as_ref())
+                .or_else(|| palette.next())
+                .map_or(crate::BLACK, String::as_str),
+        };
+        if let Some((_, last)) = previous
+            && run.is_none()
+        {
+            json.push(json!({
+                "full_text": cfg.glyph,
+                "name": "separator",
+                "color": background,
+                "background": last,
+                "separator": false,
+                "separator_block_width": 0,
+            }));
+        }
+        let mut value = block.to_json(style);
+        value["background"] = json!(background);
+        value["separator"] = json!(false);
+        value["separator_block_width"] = json!(0);
+        json.push(value);
+        previous = Some((block, background));
+    }
+    json
+}
+
+fn lemonbar_block(block: &Block, config: &Config) -> String {
+    // A lone `%` would start a formatting tag.
+    let mut text = block.full_text(&config.style).replace('%', "%%");
+    if let Some(color) = &block.color {
+        text = format!("%{{F{color}}}{text}%{{F-}}");
+    }
+    if block.urgent {
+        text = format!("%{{B{}}}{text}%{{B-}}", crate::RED);
+    }
+    text
+}
+
+fn tmux_block(block: &Block, config: &Config) -> String {
+    // `#` starts a format in a tmux status line.
+    let mut text = block.full_text(&config.style).replace('#', "##");
+    if let Some(color) = &block.color {
+        text = format!("#[fg={color}]{text}#[fg=default]");
+    }
+    if block.urgent {
+        text = format!("#[bg={}]{text}#[bg=default]", crate::RED);
+    }
+    text
+}
+
+/// Replace every `{name}` in `template` with that block's icon and text.
+/// Blocks that exist but are hidden this tick leave an empty string; names no
+/// block could have are kept literally and warned about once.
+fn fill_template(template: &str, blocks: &[Block], config: &Config) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &Captures| {
+            let name = &caps[1];
+            let parts: Vec<String> = blocks
+                .iter()
+                .filter(|b| b.name == name)
+                .map(|b| b.body(&config.style))
+                .collect();
+            if !parts.is_empty() {
+                return parts.join(" ");
+            }
+            // Checked against every built-in block, so a placeholder for one
+            // that is merely hidden right now can be told apart from a typo.
+            let known = BUILTIN_BLOCKS.iter().any(|(builtin, _)| *builtin == name)
+                || config.scripts.contains_key(name)
+                || config.merges.contains_key(name);
+            if known {
+                return String::new();
+            }
+            if WARNED.lock().unwrap().insert(name.to_string()) {
+                eprintln!("rocketbar: template: unknown block {{{name}}}");
+            }
+            caps[0].to_string()
+        })
+        .into_owned()
+}
+
+/// A hidden block prints empty text, which makes Waybar hide the module.
+/// The class follows the threshold colors so CSS can style the states.
+fn waybar_block(block: Option<&Block>, config: &Config) -> Value {
+    let Some(block) = block else {
+        return json!({ "text": "" });
+    };
+    let class = if block.urgent || block.color.as_deref() == Some(crate::RED) {
+        "critical"
+    } else if block.color.as_deref() == Some(crate::YELLOW) {
+        "warning"
+    } else {
+        ""
+    };
+    let mut line = json!({
+        "text": block.full_text(&config.style),
+        "class": class,
+    });
+    if let Some(tooltip) = &block.tooltip {
+        line["tooltip"] = json!(tooltip);
+    }
+    if let Some(percentage) = block.percentage {
+        line["percentage"] = json!(percentage.round() as u32);
+    }
+    line
+}
@@ -0,0 +1,50 @@
+//! Power source detection, so the bar can slow down while on battery.
+
+use crate::config::Config;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Whether the last check found the machine running on battery.
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+
+/// Whether a mains adapter exists and none of them is online. Desktops,
+/// which have no adapter to report, never count as on battery.
+fn on_battery() -> bool {
+    let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut adapters = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|dir| fs::read_to_string(dir.join("type")).is_ok_and(|kind| kind.trim() == "Mains"))
+        .peekable();
+    adapters.peek().is_some()
+        && adapters.all(|dir| {
+            fs::read_to_string(dir.join("online")).is_ok_and(|online| online.trim() == "0")
+        })
+}
+
+/// Check the power source again, if power saving is configured at all.
+pub fn update(config: &Config) {
+    let on_battery = config.output.battery_interval.is_some() && on_battery();
+    ON_BATTERY.store(on_battery, Ordering::Relaxed);
+}
+
+/// `secs`, stretched while on battery by as much as `output.battery_interval`
+/// is longer than `output.interval`.
+pub fn paced(secs: u64, config: &Config) -> Duration {
+    let secs = secs.max(1);
+    match config.output.battery_interval {
+        Some(slow) if ON_BATTERY.load(Ordering::Relaxed) => {
+            let fast = config.output.interval.max(1);
+            Duration::from_secs(secs * slow.max(fast) / fast)
+        }
+        _ => Duration::from_secs(secs),
+    }
+}
+
+/// Time between redraws when nothing prompts one sooner.
+pub fn tick(config: &Config) -> Duration {
+    paced(config.output.interval, config)
+}
@@ -0,0 +1,41 @@
+//! Routing table lookups from `/proc/net/route`.
+
+use std::fs;
+use std::net::Ipv4Addr;
+
+/// A default (`0.0.0.0/0`) route.
+#[derive(Clone, PartialEq)]
+pub struct Route {
+    pub iface: String,
+    pub gateway: Ipv4Addr,
+    pub metric: u32,
+}
+
+/// The default route with the lowest metric, if any.
+pub fn default_route() -> Option<Route> {
+    let data = fs::read_to_string("/proc/net/route").ok()?;
+    data.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (iface, dest, gateway, metric) = (
+                fields.first()?,
+                fields.get(1)?,
+                fields.get(2)?,
+                fields.get(6)?,
+            );
+            if *dest != "00000000" {
+                return None;
+            }
+            // Addresses are printed as hex in host (little-endian) byte order.
+            let gateway = u32::from_str_radix(gateway, 16).ok()?;
+            Some(Route {
+                iface: iface.to_string(),
+                gateway: Ipv4Addr::from(gateway.to_le_bytes()),
+                metric: metric.parse().ok()?,
+            })
+        })
+        .min_by_key(|route| route.metric)
+}
+
+pub const ICON: &str = "󰑪";
@@ -0,0 +1,60 @@
+//! Whether block values are going up or down from one tick to the next.
+
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Trend::Rising => "▲",
+            Trend::Falling => "▼",
+            Trend::Steady => "▬",
+        }
+    }
+}
+
+struct Entry {
+    last: f32,
+    trend: Trend,
+}
+
+/// The last value and direction of each block, kept across ticks.
+#[derive(Default)]
+pub struct Trends(HashMap<String, Entry>);
+
+impl Trends {
+    /// Record this tick's `value` for `key` and compare it with the last
+    /// one, counting a change of at most `deadband` as steady. With `hold`,
+    /// a steady reading keeps the direction of the last change instead, for
+    /// values like battery charge that change far less often than the bar
+    /// redraws.
+    pub fn update(&mut self, key: &str, value: f32, deadband: f32, hold: bool) -> Trend {
+        let Some(entry) = self.0.get_mut(key) else {
+            self.0.insert(
+                key.to_string(),
+                Entry {
+                    last: value,
+                    trend: Trend::Steady,
+                },
+            );
+            return Trend::Steady;
+        };
+        let trend = if value - entry.last > deadband {
+            Trend::Rising
+        } else if entry.last - value > deadband {
+            Trend::Falling
+        } else if hold {
+            entry.trend
+        } else {
+            Trend::Steady
+        };
+        *entry = Entry { last: value, trend };
+        trend
+    }
+}
@@ -0,0 +1,60 @@
+//! Shows on the bar when the render loop has stopped finishing ticks, e.g.
+//! because a block hangs, rather than leaving the last line up as if all
+//! were well.
+
+use crate::block::Block;
+use crate::config::SharedConfig;
+use crate::output::{self, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// When the render loop is due to finish its next tick, and the blocks it
+/// built last.
+pub struct Heartbeat {
+    due: Instant,
+    blocks: Vec<Block>,
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self {
+            due: Instant::now(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Record a finished tick, with the next one coming after `sleep`.
+    pub fn beat(&mut self, blocks: Vec<Block>, sleep: Duration) {
+        self.due = Instant::now() + sleep;
+        self.blocks = blocks;
+    }
+}
+
+/// Check the heartbeat every interval. Once the next tick is more than an
+/// interval late, print the last blocks again with a red `stalled` block
+/// added; the render loop's next line replaces it when it recovers.
+pub fn spawn(output: Arc<Mutex<Output>>, heartbeat: Arc<Mutex<Heartbeat>>, config: SharedConfig) {
+    thread::spawn(move || {
+        let mut reported = false;
+        loop {
+            let config = config.read().unwrap().clone();
+            let interval = crate::power::tick(&config);
+            thread::sleep(interval);
+            if !config.output.watchdog {
+                continue;
+            }
+            let (late, mut blocks) = {
+                let heartbeat = heartbeat.lock().unwrap();
+                (heartbeat.due.elapsed(), heartbeat.blocks.clone())
+            };
+            let stalled = late > interval;
+            if stalled && !reported {
+                eprintln!("rocketbar: status update overdue by {}s", late.as_secs());
+                blocks.push(Block::new("stalled", "", "stalled").color(crate::RED));
+                output::print(&mut output.lock().unwrap(), &blocks, &config);
+            }
+            reported = stalled;
+        }
+    });
+}
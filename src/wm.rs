@@ -0,0 +1,192 @@
+//! A small client for the i3 IPC protocol, spoken by both sway and i3.
+//!
+//! Every message is the magic string `i3-ipc`, the payload length and the
+//! message type as native-endian `u32`s, then a JSON payload. Events share
+//! the framing and have the highest bit of their type set.
+
+use crate::command;
+use serde_json::Value;
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const GET_TREE: u32 = 4;
+const GET_BINDING_STATE: u32 = 12;
+const SUBSCRIBE: u32 = 2;
+const EVENT_BIT: u32 = 1 << 31;
+
+/// The IPC socket of the running window manager: `$SWAYSOCK`, `$I3SOCK`, or
+/// whatever `i3 --get-socketpath` reports.
+pub fn socket_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("SWAYSOCK").or_else(|| env::var_os("I3SOCK")) {
+        return Some(path.into());
+    }
+    // Not having i3 installed is the common case, so this is not logged.
+    let output = command::output_with_timeout(
+        Command::new("i3").arg("--get-socketpath"),
+        crate::COMMAND_TIMEOUT,
+    )
+    .ok()?;
+    let path = String::from_utf8(output.stdout).ok()?;
+    let path = path.trim();
+    (output.status.success() && !path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Whether a window manager IPC socket is available.
+pub fn available() -> bool {
+    socket_path().is_some()
+}
+
+/// A connection to the window manager's IPC socket.
+pub struct Connection(UnixStream);
+
+impl Connection {
+    pub fn connect() -> io::Result<Self> {
+        let path = socket_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no window manager socket"))?;
+        Ok(Self(UnixStream::connect(path)?))
+    }
+
+    fn send(&mut self, kind: u32, payload: &str) -> io::Result<()> {
+        let mut message = Vec::with_capacity(14 + payload.len());
+        message.extend_from_slice(MAGIC);
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&kind.to_ne_bytes());
+        message.extend_from_slice(payload.as_bytes());
+        self.0.write_all(&message)
+    }
+
+    /// Read the next message, returning its type and parsed payload.
+    fn receive(&mut self) -> io::Result<(u32, Value)> {
+        let mut header = [0u8; 14];
+        self.0.read_exact(&mut header)?;
+        if &header[..6] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "bad i3-ipc magic",
+            ));
+        }
+        let len = u32::from_ne_bytes(header[6..10].try_into().unwrap());
+        let kind = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+        let mut payload = vec![0u8; len as usize];
+        self.0.read_exact(&mut payload)?;
+        Ok((kind, serde_json::from_slice(&payload)?))
+    }
+
+    /// Send a request and wait for its reply.
+    pub fn request(&mut self, kind: u32, payload: &str) -> io::Result<Value> {
+        self.send(kind, payload)?;
+        loop {
+            let (reply_kind, reply) = self.receive()?;
+            if reply_kind == kind {
+                return Ok(reply);
+            }
+        }
+    }
+}
+
+/// The full layout tree, as returned by `get_tree`.
+pub fn get_tree() -> Option<Value> {
+    Connection::connect().ok()?.request(GET_TREE, "").ok()
+}
+
+/// The name of the active binding mode, `default` outside of any.
+pub fn get_binding_mode() -> Option<String> {
+    let reply = Connection::connect()
+        .ok()?
+        .request(GET_BINDING_STATE, "")
+        .ok()?;
+    reply["name"].as_str().map(str::to_string)
+}
+
+/// Tiling and floating children of a node.
+fn children(node: &Value) -> impl Iterator<Item = &Value> {
+    ["nodes", "floating_nodes"]
+        .into_iter()
+        .filter_map(|key| node[key].as_array())
+        .flatten()
+}
+
+/// The focused node of `tree`.
+pub fn find_focused(tree: &Value) -> Option<&Value> {
+    if tree["focused"].as_bool() == Some(true) {
+        return Some(tree);
+    }
+    children(tree).find_map(find_focused)
+}
+
+/// The node holding the focused node, whose layout is the one a `layout`
+/// command would change. A focused empty workspace is its own container.
+pub fn find_focused_container(tree: &Value) -> Option<&Value> {
+    if tree["focused"].as_bool() == Some(true) {
+        return Some(tree);
+    }
+    children(tree).find_map(|child| {
+        if child["focused"].as_bool() == Some(true) && child["type"] != "workspace" {
+            Some(tree)
+        } else {
+            find_focused_container(child)
+        }
+    })
+}
+
+/// Longest wait between attempts to reconnect to the window manager.
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+
+/// Keep a subscription to `events` up for good: call `seed` to read the
+/// current state, then `on_event` for each event as it arrives. When the
+/// window manager restarts or goes away, reconnect after a delay that
+/// doubles up to a minute and seed again, so nothing missed in between
+/// stays shown. Errors are logged under `name`, each only once in a row.
+pub fn follow(
+    name: &str,
+    events: &[&str],
+    mut seed: impl FnMut(),
+    mut on_event: impl FnMut(Value),
+) -> ! {
+    let mut delay = Duration::from_secs(1);
+    let mut last_error = None;
+    loop {
+        let started = Instant::now();
+        seed();
+        match subscribe(events, &mut on_event) {
+            Ok(()) => last_error = None,
+            Err(e) => {
+                let message = e.to_string();
+                if last_error.as_ref() != Some(&message) {
+                    eprintln!("rocketbar: {name}: {message}; reconnecting");
+                    last_error = Some(message);
+                }
+            }
+        }
+        // A subscription that held for a while was not a failing one.
+        if started.elapsed() > RECONNECT_MAX {
+            delay = Duration::from_secs(1);
+        }
+        thread::sleep(delay);
+        delay = (delay * 2).min(RECONNECT_MAX);
+    }
+}
+
+/// Subscribe to `events` and call `on_event` for each one as it arrives.
+/// Returns once the subscription ends, e.g. when the window manager exits.
+pub fn subscribe(events: &[&str], mut on_event: impl FnMut(Value)) -> io::Result<()> {
+    let mut conn = Connection::connect()?;
+    let reply = conn.request(SUBSCRIBE, &serde_json::to_string(events)?)?;
+    if reply["success"].as_bool() != Some(true) {
+        return Err(io::Error::other(format!("subscribe failed: {reply}")));
+    }
+    loop {
+        match conn.receive() {
+            Ok((kind, event)) if kind & EVENT_BIT != 0 => on_event(event),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}